@@ -2,5 +2,10 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Go up one level from tools/ to project root, then into src/logger_proto/
     tonic_build::compile_protos("proto/log_service.proto")?;
+    // OTLP logs receiver, generated into nested modules matching the proto packages
+    tonic_build::configure().build_client(false).compile(
+        &["proto/opentelemetry/proto/collector/logs/v1/logs_service.proto"],
+        &["proto"],
+    )?;
     Ok(())
-}
\ No newline at end of file
+}
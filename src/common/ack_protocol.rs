@@ -0,0 +1,143 @@
+//! Acknowledged TCP protocol (opt-in)
+//!
+//! A client opts in by sending a hello frame first : `LSAK`, version, ack mode, ack stage. The
+//! server answers `LSAK` + version, then every frame carries an 8-byte big-endian message ID ahead
+//! of the packed `LoggerMsg`, and the server writes ack frames back on the same connection.
+//! Connections that do not start with a hello keep the fire-and-forget protocol.
+
+use crate::core::writers::AckStage;
+
+
+
+
+/// First bytes of the hello frame and of its reply
+///
+/// Cannot start a valid packed Cap'n Proto message : as a segment table it would announce more
+/// than 65536 segments.
+pub const ACK_PROTOCOL_MAGIC: &[u8; 4] = b"LSAK";
+
+/// Version of the acknowledged protocol
+pub const ACK_PROTOCOL_VERSION: u8 = 1;
+
+/// Size of the message ID prefix of every frame in acknowledged mode
+pub const MESSAGE_ID_BYTES: usize = 8;
+
+//-----------------------------------------------------------------------------------------------
+
+/// How acknowledged messages are reported back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckMode {
+    /// One ack frame per message ID
+    PerMessage,
+    /// One ack frame covering every message sent up to (and including) an ID
+    Cumulative,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Settings requested by the client hello
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AckHello {
+    pub mode: AckMode,
+    pub stage: AckStage,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl AckHello {
+    /// Parse a first frame : None if it is not a hello (legacy message), Err if the hello is invalid
+    pub fn parse(payload: &[u8]) -> Option<Result<Self, String>> {
+        let fields = payload.strip_prefix(ACK_PROTOCOL_MAGIC)?;
+        let [version, mode, stage] = fields else {
+            return Some(Err(format!("hello of {} bytes, expected 7", payload.len())));
+        };
+        if *version != ACK_PROTOCOL_VERSION {
+            return Some(Err(format!("unsupported protocol version {}", version)));
+        }
+        let mode = match mode {
+            0 => AckMode::PerMessage,
+            1 => AckMode::Cumulative,
+            _ => return Some(Err(format!("invalid ack mode {}", mode))),
+        };
+        let stage = match stage {
+            0 => AckStage::Queued,
+            1 => AckStage::Written,
+            2 => AckStage::Durable,
+            _ => return Some(Err(format!("invalid ack stage {}", stage))),
+        };
+        Some(Ok(Self { mode, stage }))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Reply frame accepting the hello
+    pub fn reply() -> Vec<u8> {
+        let mut reply = ACK_PROTOCOL_MAGIC.to_vec();
+        reply.push(ACK_PROTOCOL_VERSION);
+        reply
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Frame written back by the server in acknowledged mode : kind byte, message ID, optional reason
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AckFrame {
+    /// The message reached the requested stage
+    Ack(u64),
+    /// Every message sent up to and including this one reached the requested stage or was nacked
+    AckUpTo(u64),
+    /// The message was rejected (malformed, or the writer failed) and will not be written
+    Nack(u64, String),
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl AckFrame {
+    const KIND_ACK: u8 = 1;
+    const KIND_ACK_UP_TO: u8 = 2;
+    const KIND_NACK: u8 = 3;
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Encode the frame payload
+    pub fn encode(&self) -> Vec<u8> {
+        let (kind, id, reason) = match self {
+            AckFrame::Ack(id) => (Self::KIND_ACK, id, ""),
+            AckFrame::AckUpTo(id) => (Self::KIND_ACK_UP_TO, id, ""),
+            AckFrame::Nack(id, reason) => (Self::KIND_NACK, id, reason.as_str()),
+        };
+        let mut payload = Vec::with_capacity(1 + MESSAGE_ID_BYTES + reason.len());
+        payload.push(kind);
+        payload.extend_from_slice(&id.to_be_bytes());
+        payload.extend_from_slice(reason.as_bytes());
+        payload
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Decode a frame payload, used by clients
+    pub fn decode(payload: &[u8]) -> Result<Self, String> {
+        let (&kind, rest) = payload.split_first().ok_or("empty ack frame")?;
+        let (id, reason) = split_message_id(rest)?;
+        match kind {
+            Self::KIND_ACK => Ok(AckFrame::Ack(id)),
+            Self::KIND_ACK_UP_TO => Ok(AckFrame::AckUpTo(id)),
+            Self::KIND_NACK => Ok(AckFrame::Nack(id, String::from_utf8_lossy(reason).into_owned())),
+            _ => Err(format!("unknown ack frame kind {}", kind)),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Split the message ID prefix from the rest of a frame
+pub fn split_message_id(payload: &[u8]) -> Result<(u64, &[u8]), String> {
+    if payload.len() < MESSAGE_ID_BYTES {
+        return Err(format!("frame of {} bytes too short for a message ID", payload.len()));
+    }
+    let (id, rest) = payload.split_at(MESSAGE_ID_BYTES);
+    let mut id_buf = [0u8; MESSAGE_ID_BYTES];
+    id_buf.copy_from_slice(id);
+    Ok((u64::from_be_bytes(id_buf), rest))
+}
\ No newline at end of file
@@ -0,0 +1,159 @@
+//! Token authentication of clients
+//!
+//! Tokens are loaded from a file, one `<identity> <token>` pair per line (`#` starts a comment).
+//! TCP clients authenticate with an auth frame before any message, gRPC clients with an
+//! `authorization: Bearer <token>` header, Fluent Forward clients with the token as shared key.
+//! The identity is stamped on every record they send.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+
+
+
+/// First bytes of the TCP auth frame, followed by the UTF-8 token
+///
+/// Like the ack protocol hello, cannot start a valid packed Cap'n Proto message.
+pub const AUTH_FRAME_MAGIC: &[u8; 4] = b"LSAU";
+
+/// Status byte of the auth reply : token accepted
+pub const AUTH_ACCEPTED: u8 = 1;
+
+/// Status byte of the auth reply : token rejected, the connection is closed
+pub const AUTH_REJECTED: u8 = 0;
+
+/// Time a TCP client has to send its auth frame once connected
+pub const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+//-----------------------------------------------------------------------------------------------
+
+/// Client identity of an authenticated connection or request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientIdentity(pub String);
+
+//-----------------------------------------------------------------------------------------------
+
+/// Accepted tokens and the client identity each one maps to
+#[derive(Clone, Debug, Default)]
+pub struct TokenAuth {
+    identities: HashMap<String, String>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl TokenAuth {
+    /// Load the token file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {} - {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| format!("{} - {}", path.display(), e))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Parse `<identity> <token>` lines, blank lines and `#` comments are ignored
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut identities = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let (Some(identity), Some(token), None) = (parts.next(), parts.next(), parts.next()) else {
+                return Err(format!("line {} : expected '<identity> <token>'", index + 1));
+            };
+            if identities.insert(token.to_string(), identity.to_string()).is_some() {
+                return Err(format!("line {} : duplicate token", index + 1));
+            }
+        }
+        if identities.is_empty() {
+            return Err("no token defined".to_string());
+        }
+        Ok(Self { identities })
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Client identity of `token`, None if the token is unknown
+    ///
+    /// Every known token is compared in constant time, so timing does not reveal partial matches.
+    pub fn identify(&self, token: &str) -> Option<ClientIdentity> {
+        let mut found = None;
+        for (known, identity) in &self.identities {
+            if constant_time_eq(known.as_bytes(), token.as_bytes()) {
+                found = Some(identity);
+            }
+        }
+        found.map(|identity| ClientIdentity(identity.clone()))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Identity of a TCP auth frame payload, Err for a frame that is not an auth frame
+    pub fn identify_frame(&self, payload: &[u8]) -> Result<Option<ClientIdentity>, String> {
+        let token = payload
+            .strip_prefix(AUTH_FRAME_MAGIC)
+            .ok_or("authentication required, first frame is not an auth frame")?;
+        let token = std::str::from_utf8(token).map_err(|_| "token is not valid UTF-8")?;
+        Ok(self.identify(token))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Identity of an `authorization` header value (`Bearer <token>`)
+    pub fn identify_bearer(&self, header: &str) -> Option<ClientIdentity> {
+        let token = header.strip_prefix("Bearer ").or_else(|| header.strip_prefix("bearer "))?;
+        self.identify(token.trim())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Identity and token proven by a digest, for handshakes where the token itself is not sent
+    ///
+    /// `digest_of` computes the digest a client holding a given token would send. Every token's
+    /// digest is compared in constant time, as in `identify`.
+    pub fn identify_digest(&self, digest: &str, digest_of: impl Fn(&str) -> String) -> Option<(ClientIdentity, String)> {
+        let mut found = None;
+        for (known, identity) in &self.identities {
+            if constant_time_eq(digest_of(known).as_bytes(), digest.as_bytes()) {
+                found = Some((ClientIdentity(identity.clone()), known.clone()));
+            }
+        }
+        found
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Number of configured tokens
+    pub fn len(&self) -> usize {
+        self.identities.len()
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// True when no token is configured
+    pub fn is_empty(&self) -> bool {
+        self.identities.is_empty()
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Reply frame to a TCP auth frame
+pub fn auth_reply(accepted: bool) -> Vec<u8> {
+    let mut reply = AUTH_FRAME_MAGIC.to_vec();
+    reply.push(if accepted { AUTH_ACCEPTED } else { AUTH_REJECTED });
+    reply
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper comparing two byte strings in time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
\ No newline at end of file
@@ -1,8 +1,24 @@
 //! Server configuration
 
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::common::auth::TokenAuth;
+use crate::common::tls::TlsConfig;
 
 
+
+/// Default maximum size of a TCP frame payload (16 MiB)
+pub const DEFAULT_MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default maximum size of a UDP datagram, the largest UDP payload
+pub const DEFAULT_MAX_DATAGRAM_BYTES: usize = 65507;
+
+/// Default permissions of the Unix socket : owner and group may connect
+pub const DEFAULT_UNIX_SOCKET_MODE: u32 = 0o660;
+
+//-----------------------------------------------------------------------------------------------
+
 /// Server configuration
 #[derive(Clone)]
 pub struct ServerConfig {
@@ -10,6 +26,28 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub grpc_port: u16,
+    /// Frames announcing a larger payload are rejected and the connection closed
+    pub max_frame_bytes: usize,
+    /// TLS for the TCP and gRPC listeners, None listens in plaintext
+    pub tls: Option<TlsConfig>,
+    /// Accepted client tokens, None accepts unauthenticated clients
+    pub auth: Option<Arc<TokenAuth>>,
+    /// UDP port, None disables the UDP listener
+    pub udp_port: Option<u16>,
+    /// Larger datagrams are dropped
+    pub max_datagram_bytes: usize,
+    /// HTTP port of the JSON endpoint, None disables the HTTP listener
+    pub http_port: Option<u16>,
+    /// Syslog port (UDP and TCP), None disables the syslog listener
+    pub syslog_port: Option<u16>,
+    /// Fluent Forward port, None disables the forward listener
+    pub forward_port: Option<u16>,
+    /// GELF port (UDP and TCP), None disables the GELF listener
+    pub gelf_port: Option<u16>,
+    /// Unix domain socket path, None disables the Unix socket listener
+    pub unix_socket: Option<PathBuf>,
+    /// File permissions of the Unix socket
+    pub unix_socket_mode: u32,
 }
 
 //-----------------------------------------------------------------------------------------------
@@ -22,6 +60,17 @@ impl ServerConfig {
             host: host.to_string(),
             port,
             grpc_port,
+            max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+            tls: None,
+            auth: None,
+            udp_port: None,
+            max_datagram_bytes: DEFAULT_MAX_DATAGRAM_BYTES,
+            http_port: None,
+            syslog_port: None,
+            forward_port: None,
+            gelf_port: None,
+            unix_socket: None,
+            unix_socket_mode: DEFAULT_UNIX_SOCKET_MODE,
         }
     }
 }
\ No newline at end of file
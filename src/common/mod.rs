@@ -1,4 +1,8 @@
 //! Common utilities and shared components
 
+pub mod ack_protocol;
+pub mod auth;
 pub mod config;
-pub mod safe_socket;
\ No newline at end of file
+pub mod safe_socket;
+pub mod stats;
+pub mod tls;
\ No newline at end of file
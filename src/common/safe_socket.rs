@@ -1,56 +1,120 @@
 //! Safe TCP socket wrapper with message framing
+//!
+//! Frames are a big-endian u32 length prefix followed by the payload. Decoding goes through
+//! `FrameCodec`, so prefixes and payloads split across TCP segments are reassembled exactly.
 
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::io::{self, AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
-use bytes::{BytesMut, BufMut};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+use bytes::{Buf, BufMut, BytesMut};
+use futures_util::{SinkExt, StreamExt};
 
 
 
 
 
-/// Safe TCP socket with message framing
-pub struct SafeSocket {
-    conn: TcpStream,
+/// Size of the frame length prefix
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+//-----------------------------------------------------------------------------------------------
+
+/// Length-delimited frame codec : big-endian u32 length prefix, then the payload
+#[derive(Clone, Copy, Debug)]
+pub struct FrameCodec {
+    max_frame_bytes: usize,
 }
 
 //-----------------------------------------------------------------------------------------------
 
-impl SafeSocket {
-    /// Create new safe socket
-    pub fn new(conn: TcpStream) -> Self {
-        SafeSocket { conn }
+impl FrameCodec {
+    /// Create a codec rejecting payloads larger than `max_frame_bytes`
+    pub fn new(max_frame_bytes: usize) -> Self {
+        Self { max_frame_bytes }
     }
-    
-    //-----------------------------------------------------------------------------------------------
-    
-    /// Receive framed data from socket
-    pub async fn receive_data(&mut self) -> io::Result<Option<BytesMut>> {
-        // big-endian u32 length prefix
-        let mut length_buf = [0u8; 4];
-        let n = self.conn.read(&mut length_buf).await?; 
-        if n < 4 {
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Decoder for FrameCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    /// Decode one frame, Ok(None) until the prefix and the whole payload are buffered
+    ///
+    /// An oversized frame is reported as an `InvalidData` error before anything is allocated for it.
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if src.len() < LENGTH_PREFIX_BYTES {
             return Ok(None);
         }
+        let mut length_buf = [0u8; LENGTH_PREFIX_BYTES];
+        length_buf.copy_from_slice(&src[..LENGTH_PREFIX_BYTES]);
         let slen = u32::from_be_bytes(length_buf) as usize;
-        let mut chunk = BytesMut::with_capacity(slen);
-        
-        while chunk.len() < slen {
-            let remaining = slen - chunk.len();
-            let mut buf = vec![0u8; remaining];
-            let n = self.conn.read(&mut buf).await?; 
-            if n == 0 {
-                return Ok(None);
-            }
-            chunk.put_slice(&buf[..n]);
+        if slen > self.max_frame_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame of {} bytes exceeds the {} bytes limit", slen, self.max_frame_bytes),
+            ));
         }
-        Ok(Some(chunk))
+
+        if src.len() < LENGTH_PREFIX_BYTES + slen {
+            // Make room for the rest of the frame in one go
+            src.reserve(LENGTH_PREFIX_BYTES + slen - src.len());
+            return Ok(None);
+        }
+        src.advance(LENGTH_PREFIX_BYTES);
+        Ok(Some(src.split_to(slen)))
     }
 }
 
 //-----------------------------------------------------------------------------------------------
 
-impl Drop for SafeSocket {
-    fn drop(&mut self) {
-        let _ = self.conn.shutdown();
+impl Encoder<&[u8]> for FrameCodec {
+    type Error = io::Error;
+
+    /// Encode one frame, used by clients and tests
+    fn encode(&mut self, payload: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        if payload.len() > self.max_frame_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame of {} bytes exceeds the {} bytes limit", payload.len(), self.max_frame_bytes),
+            ));
+        }
+        dst.reserve(LENGTH_PREFIX_BYTES + payload.len());
+        dst.put_u32(payload.len() as u32);
+        dst.put_slice(payload);
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Safe TCP socket with message framing, over a plain or TLS stream
+pub struct SafeSocket<S = TcpStream> {
+    frames: Framed<S, FrameCodec>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SafeSocket<S> {
+    /// Create new safe socket, frames larger than `max_frame_bytes` are rejected
+    pub fn new(conn: S, max_frame_bytes: usize) -> Self {
+        SafeSocket { frames: Framed::new(conn, FrameCodec::new(max_frame_bytes)) }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Receive framed data from socket
+    ///
+    /// Returns None when the peer closed the connection between frames. A connection closed in the
+    /// middle of a frame, or an oversized frame (`InvalidData`), is an error.
+    pub async fn receive_data(&mut self) -> io::Result<Option<BytesMut>> {
+        self.frames.next().await.transpose()
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Send one framed payload to the peer
+    pub async fn send_data(&mut self, payload: &[u8]) -> io::Result<()> {
+        self.frames.send(payload).await
     }
 }
\ No newline at end of file
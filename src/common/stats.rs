@@ -0,0 +1,50 @@
+//! Ingestion counters shared by the protocol servers, and their rate-limited report on stderr
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+
+
+
+/// Rejected messages are reported once per this many, a flood must not flood stderr too
+pub const REPORT_EVERY: u64 = 1000;
+
+//-----------------------------------------------------------------------------------------------
+
+/// Ingestion counters, shared by every protocol server
+#[derive(Default)]
+pub struct ServerStats {
+    /// TCP frames rejected because their length prefix exceeds the maximum frame size
+    pub oversized_frames: AtomicU64,
+    /// Connections and requests rejected for a missing or unknown token
+    pub auth_failures: AtomicU64,
+    /// UDP datagrams that did not decode as a packed Cap'n Proto message
+    pub malformed_datagrams: AtomicU64,
+    /// UDP datagrams dropped for their size or a full writer queue
+    pub dropped_datagrams: AtomicU64,
+    /// Syslog messages (UDP or TCP) rejected by the syslog parser
+    pub malformed_syslog_messages: AtomicU64,
+    /// Syslog UDP datagrams dropped because the writer queue was full
+    pub dropped_syslog_messages: AtomicU64,
+    /// Fluent Forward messages, and entries skipped within a message, rejected by the forward decoder
+    pub malformed_forward_messages: AtomicU64,
+    /// GELF messages (UDP or TCP) rejected by the chunk reassembly or the GELF parser
+    pub malformed_gelf_messages: AtomicU64,
+    /// Chunked GELF messages dropped because their chunks did not all arrive in time
+    pub incomplete_gelf_messages: AtomicU64,
+    /// GELF UDP messages dropped because the writer queue was full
+    pub dropped_gelf_messages: AtomicU64,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Add `added` rejected messages to `counter`, reported on the first and then once per `REPORT_EVERY`
+///
+/// Returns whether this call was reported. `name` is the server name, `detail` what was rejected.
+pub fn report_rejected(name: &str, counter: &AtomicU64, added: u64, kind: &str, detail: &str) -> bool {
+    let count = counter.fetch_add(added, Ordering::Relaxed) + added;
+    let reported = added > 0 && (count == added || count / REPORT_EVERY > (count - added) / REPORT_EVERY);
+    if reported {
+        eprintln!("{} : {} {} ({} in total)", name, kind, detail, count);
+    }
+    reported
+}
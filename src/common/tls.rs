@@ -0,0 +1,164 @@
+//! TLS configuration and handshake of the listeners
+//!
+//! Every listener shares one certificate and key. With a client CA the server verifies client
+//! certificates (mutual TLS) and records the client certificate subject as message source.
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient};
+use tokio_rustls::rustls::{self, Certificate, PrivateKey, RootCertStore};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tonic::transport::{Certificate as TonicCertificate, Identity, ServerTlsConfig};
+
+
+
+
+/// Time allowed to a client to complete the TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+//-----------------------------------------------------------------------------------------------
+
+/// Certificate, key and optional client verification of the listeners
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    /// PEM certificate chain presented by the server
+    pub cert_path: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1) of the server certificate
+    pub key_path: PathBuf,
+    /// PEM CA certificates verifying client certificates, None disables client authentication
+    pub client_ca_path: Option<PathBuf>,
+    /// Accept clients without a certificate (those presenting one are still verified)
+    pub client_auth_optional: bool,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl TlsConfig {
+    /// Create a server-only TLS configuration
+    pub fn new(cert_path: &Path, key_path: &Path) -> Self {
+        Self {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            client_ca_path: None,
+            client_auth_optional: false,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// rustls configuration of the Cap'n Proto TCP listener
+    pub fn rustls_server_config(&self) -> Result<Arc<rustls::ServerConfig>, String> {
+        let certs = read_certificates(&self.cert_path)?;
+        let key = read_private_key(&self.key_path)?;
+
+        let builder = rustls::ServerConfig::builder().with_safe_defaults();
+        let builder = match &self.client_ca_path {
+            Some(client_ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in read_certificates(client_ca_path)? {
+                    roots
+                        .add(&cert)
+                        .map_err(|e| format!("invalid client CA in {} - {}", client_ca_path.display(), e))?;
+                }
+                let verifier = if self.client_auth_optional {
+                    AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed()
+                } else {
+                    AllowAnyAuthenticatedClient::new(roots).boxed()
+                };
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let config = builder
+            .with_single_cert(certs, key)
+            .map_err(|e| format!("invalid certificate or key - {}", e))?;
+        Ok(Arc::new(config))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// tonic configuration of the gRPC listener
+    pub fn tonic_server_config(&self) -> Result<ServerTlsConfig, String> {
+        let cert = read_file(&self.cert_path)?;
+        let key = read_file(&self.key_path)?;
+
+        let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(client_ca_path) = &self.client_ca_path {
+            config = config
+                .client_ca_root(TonicCertificate::from_pem(read_file(client_ca_path)?))
+                .client_auth_optional(self.client_auth_optional);
+        }
+        Ok(config)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// TLS handshake of an accepted connection, bounded so a silent client cannot hold it open
+///
+/// Returns the stream with, under mTLS, the client certificate subject identifying the source.
+pub async fn accept(tls_acceptor: &TlsAcceptor, socket: TcpStream) -> io::Result<(TlsStream<TcpStream>, Option<String>)> {
+    let tls_stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(socket))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "handshake timed out"))??;
+    let subject = tls_stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .and_then(|cert| certificate_subject(&cert.0));
+    Ok((tls_stream, subject))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Subject of a DER client certificate (e.g. `CN=trader-01, O=Desk`), used as message source
+pub fn certificate_subject(der: &[u8]) -> Option<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+    Some(cert.subject().to_string())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to read a whole file, with its path in the error
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("cannot read {} - {}", path.display(), e))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to read every certificate of a PEM file
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>, String> {
+    let file = File::open(path).map_err(|e| format!("cannot read {} - {}", path.display(), e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .map_err(|e| format!("invalid PEM in {} - {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("no certificate found in {}", path.display()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to read the first private key of a PEM file
+fn read_private_key(path: &Path) -> Result<PrivateKey, String> {
+    let file = File::open(path).map_err(|e| format!("cannot read {} - {}", path.display(), e))?;
+    let items = rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|e| format!("invalid PEM in {} - {}", path.display(), e))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("no private key found in {}", path.display()))
+}
\ No newline at end of file
@@ -0,0 +1,143 @@
+//! Compression of rotated log files
+//!
+//! Rotated files are compressed off the writer task (blocking thread pool) with gzip or zstd.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+
+
+
+/// Suffix of an archive being written, renamed to its final name once complete
+pub const PART_SUFFIX: &str = ".part";
+
+//-----------------------------------------------------------------------------------------------
+
+/// Codec used for rotated log files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Compression {
+    /// Every supported codec
+    pub const ALL: [Compression; 2] = [Compression::Gzip, Compression::Zstd];
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Look up a codec by name (`gzip` or `zstd`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gzip" => Some(Compression::Gzip),
+            "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Codec of an already compressed file, from its suffix
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy();
+        Self::ALL.into_iter().find(|codec| name.ends_with(codec.suffix()))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// File name suffix added to compressed files
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Compress `path` into `<path><suffix>` and remove the original, returns the compressed path
+    ///
+    /// Blocking : run it on the blocking thread pool. Data goes to a `.part` file first so an
+    /// interrupted compression never leaves a truncated archive under the final name. An existing
+    /// archive is never overwritten : the compression fails and the uncompressed file is kept.
+    pub fn compress_file(self, path: &Path) -> io::Result<PathBuf> {
+        let compressed_path = append_to_file_name(path, self.suffix());
+        let part_path = append_to_file_name(&compressed_path, PART_SUFFIX);
+        if compressed_path.try_exists()? {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", compressed_path.display()),
+            ));
+        }
+
+        let input_file = File::open(path)?;
+        let modified = input_file.metadata()?.modified()?;
+        let mut input = BufReader::new(input_file);
+        let output = BufWriter::new(File::create(&part_path)?);
+        let output = match self {
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?
+            }
+            Compression::Zstd => {
+                let mut encoder = zstd::stream::write::Encoder::new(output, 0)?;
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?
+            }
+        };
+        // Retention ages rotated files by modification time : the archive keeps the original one
+        let output = output.into_inner().map_err(|e| e.into_error())?;
+        output.set_modified(modified)?;
+        output.sync_all()?;
+
+        // Link then unlink rather than rename : the link fails if the archive appeared meanwhile
+        let linked = std::fs::hard_link(&part_path, &compressed_path);
+        std::fs::remove_file(&part_path)?;
+        linked?;
+        std::fs::remove_file(path)?;
+        Ok(compressed_path)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Non-empty lines of the last `max_bytes` of a compressed file (blocking, decompresses the
+    /// whole stream)
+    pub fn read_tail_lines(self, path: &Path, max_bytes: u64) -> io::Result<Vec<String>> {
+        let file = File::open(path)?;
+        let reader: Box<dyn Read> = match self {
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+        };
+
+        let mut lines = VecDeque::new();
+        let mut bytes = 0u64;
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            bytes += line.len() as u64 + 1;
+            lines.push_back(line);
+            while bytes > max_bytes && lines.len() > 1 {
+                if let Some(oldest) = lines.pop_front() {
+                    bytes -= oldest.len() as u64 + 1;
+                }
+            }
+        }
+        Ok(lines.into())
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to append a suffix to the file name of `path`
+fn append_to_file_name(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
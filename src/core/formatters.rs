@@ -0,0 +1,359 @@
+//! Output formatting of log records
+//!
+//! Records travel typed through the pipeline and are only turned into text here, at the sink.
+
+use serde_json::{Map, Value};
+
+use crate::core::records::{AttributeValue, LogField, LogRecord};
+
+
+
+
+/// Output format of the file writer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Fixed-width text columns
+    Text,
+    /// One JSON object per line (JSON Lines)
+    Json,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl OutputFormat {
+    /// Look up an output format by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Default text template, the historical fixed-width line
+///
+/// `{extras}` writes nothing unless `--text_fields` are chosen or the record carries attributes,
+/// so records of existing clients keep the historical line byte for byte.
+pub const DEFAULT_TEXT_TEMPLATE: &str = "{seq} {timestamp:<33} {hostname:<12.12} {logger_name:<15.15} \
+{level:<8.8} {filename:<20.20} {function_name:<25.25} {line_number:<6.6} {message}{extras}";
+
+/// Template placeholder expanding to the configured extra fields
+const EXTRAS_PLACEHOLDER: &str = "extras";
+
+/// JSON key of the structured attributes
+const ATTRIBUTES_KEY: &str = "attributes";
+
+//-----------------------------------------------------------------------------------------------
+
+/// Format a record as one output line (without trailing newline)
+pub fn format_record(
+    record: &LogRecord,
+    format: OutputFormat,
+    template: &TextTemplate,
+    text_fields: &[LogField],
+) -> String {
+    match format {
+        OutputFormat::Text => template.render(record, text_fields),
+        OutputFormat::Json => format_json_line(record),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Column alignment inside a padded placeholder
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Piece of a parsed text template
+#[derive(Clone, Debug)]
+enum TemplatePart {
+    Literal(String),
+    Field {
+        field: LogField,
+        align: Align,
+        width: Option<usize>,
+        max_len: Option<usize>,
+    },
+    Extras,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Text line layout parsed from a template string
+///
+/// Placeholders are `{name}` or `{name:[<|>][width][.max_len]}` where `name` is a `LogField`
+/// name, `width` pads the value and `max_len` truncates it. `{extras}` expands to the configured
+/// extra fields as ` | name=value ...`. Literal braces are written `{{` and `}}`.
+#[derive(Clone, Debug)]
+pub struct TextTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl TextTemplate {
+    /// Parse and validate a template against the known field names
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(format!("unclosed placeholder '{{{}'", spec)),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Self::parse_placeholder(&spec)?);
+                }
+                '}' => return Err("unmatched '}', use '}}' for a literal brace".to_string()),
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+
+        Ok(Self { parts })
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Render a record (without trailing newline)
+    pub fn render(&self, record: &LogRecord, extra_fields: &[LogField]) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => line.push_str(text),
+                TemplatePart::Field { field, align, width, max_len } => {
+                    let value = field.value(record);
+                    let value = match max_len {
+                        Some(max_len) => truncate(&value, *max_len),
+                        None => &value,
+                    };
+                    push_padded(&mut line, value, *align, width.unwrap_or(0));
+                }
+                TemplatePart::Extras => push_extras(&mut line, record, extra_fields),
+            }
+        }
+        line
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Whether lines start with the sequence number, needed to read it back when resuming
+    pub fn starts_with_sequence(&self) -> bool {
+        matches!(self.parts.first(), Some(TemplatePart::Field { field: LogField::Sequence, .. }))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // Parse the content of a `{...}` placeholder
+    fn parse_placeholder(spec: &str) -> Result<TemplatePart, String> {
+        let (name, format) = match spec.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format)),
+            None => (spec.trim(), None),
+        };
+
+        if name == EXTRAS_PLACEHOLDER {
+            return match format {
+                None => Ok(TemplatePart::Extras),
+                Some(_) => Err(format!("'{{{}}}' does not accept a format", EXTRAS_PLACEHOLDER)),
+            };
+        }
+        let field = LogField::from_name(name)
+            .ok_or_else(|| format!("unknown field '{}' in template", name))?;
+
+        let Some(mut format) = format else {
+            return Ok(TemplatePart::Field { field, align: Align::Left, width: None, max_len: None });
+        };
+
+        let mut align = Align::Left;
+        if let Some(rest) = format.strip_prefix('<') {
+            format = rest;
+        } else if let Some(rest) = format.strip_prefix('>') {
+            align = Align::Right;
+            format = rest;
+        }
+
+        let (width, max_len) = match format.split_once('.') {
+            Some((width, max_len)) => (width, Some(max_len)),
+            None => (format, None),
+        };
+        let parse_number = |text: &str| {
+            text.parse::<usize>()
+                .map_err(|_| format!("invalid format '{}' for field '{}'", spec, name))
+        };
+        let width = if width.is_empty() { None } else { Some(parse_number(width)?) };
+        let max_len = max_len.map(parse_number).transpose()?;
+
+        Ok(TemplatePart::Field { field, align, width, max_len })
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Default for TextTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEXT_TEMPLATE).expect("default text template is valid")
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Format the marker line for sequences `from..=to` skipped by the writer gap timeout
+///
+/// The line leads with the last skipped sequence, so numbering resumes after the gap on restart.
+pub fn format_gap(from: u64, to: u64, format: OutputFormat) -> String {
+    let skipped = to - from + 1;
+    match format {
+        OutputFormat::Text => format!(
+            "{} ---- GAP : {} missing sequence(s) {}..={} skipped ----",
+            to, skipped, from, to
+        ),
+        OutputFormat::Json => {
+            let mut object = Map::new();
+            object.insert("seq".to_string(), Value::from(to));
+            object.insert("gap_from".to_string(), Value::from(from));
+            object.insert("gap_skipped".to_string(), Value::from(skipped));
+            Value::Object(object).to_string()
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Format a record as a single-line JSON object holding every field
+pub fn format_json_line(record: &LogRecord) -> String {
+    let mut object = Map::new();
+    for field in LogField::ALL {
+        let value = match field {
+            LogField::Sequence => Value::from(record.sequence),
+            _ => Value::from(field.value(record).into_owned()),
+        };
+        object.insert(field.name().to_string(), value);
+    }
+
+    // Attributes keep their type, and are only present when the record has some
+    if !record.attributes.is_empty() {
+        let attributes = record
+            .attributes
+            .iter()
+            .map(|(key, value)| (key.clone(), attribute_json(value)))
+            .collect();
+        object.insert(ATTRIBUTES_KEY.to_string(), Value::Object(attributes));
+    }
+
+    Value::Object(object).to_string()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to append the extra fields then the attributes as ` | name=value ...`, empty values are skipped
+fn push_extras(line: &mut String, record: &LogRecord, extra_fields: &[LogField]) {
+    let mut separator = " | ";
+    for field in extra_fields {
+        let value = field.value(record);
+        if value.is_empty() {
+            continue;
+        }
+        line.push_str(separator);
+        line.push_str(field.name());
+        line.push('=');
+        push_quoted(line, &value);
+        separator = " ";
+    }
+    for (key, value) in &record.attributes {
+        line.push_str(separator);
+        push_quoted(line, key);
+        line.push('=');
+        push_quoted(line, &value.to_string());
+        separator = " ";
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to convert an attribute to JSON, a non finite float becomes a string
+fn attribute_json(value: &AttributeValue) -> Value {
+    match value {
+        AttributeValue::String(value) => Value::from(value.as_str()),
+        AttributeValue::Int(value) => Value::from(*value),
+        AttributeValue::Float(value) => serde_json::Number::from_f64(*value)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::from(value.to_string())),
+        AttributeValue::Bool(value) => Value::from(*value),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to append a value padded to `width` chars
+fn push_padded(line: &mut String, value: &str, align: Align, width: usize) {
+    let padding = width.saturating_sub(value.chars().count());
+    if align == Align::Right {
+        line.extend(std::iter::repeat_n(' ', padding));
+    }
+    line.push_str(value);
+    if align == Align::Left {
+        line.extend(std::iter::repeat_n(' ', padding));
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to truncate long strings on a char boundary
+fn truncate(s: &str, max_len: usize) -> &str {
+    match s.char_indices().nth(max_len) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to append a value, quoted and escaped when it would break the key=value layout
+fn push_quoted(line: &mut String, value: &str) {
+    let needs_quotes = value.chars().any(|c| c.is_whitespace() || c == '"' || c == '=');
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            _ => line.push(c),
+        }
+    }
+    line.push('"');
+}
@@ -0,0 +1,330 @@
+//! Fluent Forward protocol decoding
+//!
+//! Decodes the Message, Forward and (Compressed)PackedForward modes of the Fluentd forward
+//! protocol v1 into log records : the tag becomes the logger name, the event time the timestamp,
+//! well known record keys fill the schema fields and every other key is kept as a typed attribute.
+
+use std::io::Read;
+
+use chrono::{DateTime, SecondsFormat};
+use flate2::read::MultiGzDecoder;
+use rmpv::Value;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::core::otlp::severity_text_level;
+use crate::core::records::{AttributeValue, LogRecord};
+use crate::logger_capnp::logger_msg::Level;
+
+
+
+
+/// MessagePack extension type of an EventTime (seconds and nanoseconds, big endian)
+const EVENT_TIME_EXT_TYPE: i8 = 0;
+
+/// Schema field filled by a record key
+#[derive(Clone, Copy)]
+enum Target {
+    Message,
+    Level,
+    Hostname,
+    Module,
+    FileName,
+    FunctionName,
+    LineNumber,
+    PathName,
+    ProcessId,
+    ProcessName,
+    ThreadId,
+    ThreadName,
+    ServiceName,
+    StackTrace,
+}
+
+/// Record keys mapped onto schema fields : the schema names, then common Fluent Bit / Fluentd keys
+const RECORD_FIELDS: [(&str, Target); 21] = [
+    ("message", Target::Message),
+    ("level", Target::Level),
+    ("hostname", Target::Hostname),
+    ("module", Target::Module),
+    ("filename", Target::FileName),
+    ("function_name", Target::FunctionName),
+    ("line_number", Target::LineNumber),
+    ("path_name", Target::PathName),
+    ("process_id", Target::ProcessId),
+    ("process_name", Target::ProcessName),
+    ("thread_id", Target::ThreadId),
+    ("thread_name", Target::ThreadName),
+    ("service_name", Target::ServiceName),
+    ("stack_trace", Target::StackTrace),
+    ("log", Target::Message),
+    ("msg", Target::Message),
+    ("severity", Target::Level),
+    ("host", Target::Hostname),
+    ("pid", Target::ProcessId),
+    ("ident", Target::ProcessName),
+    ("service", Target::ServiceName),
+];
+
+//-----------------------------------------------------------------------------------------------
+
+/// Records of one forward message and the chunk id to acknowledge, if the client asked for it
+#[derive(Debug)]
+pub struct ForwardMessage {
+    pub records: Vec<LogRecord>,
+    /// Errors of the malformed entries skipped, the valid entries are still recorded
+    pub skipped: Vec<String>,
+    pub chunk: Option<String>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Decode one forward message received from `source`
+///
+/// The mode follows from the second array item : an event time (Message), an array of entries
+/// (Forward) or a binary / string of concatenated entries (PackedForward, gzip compressed when the
+/// option says so). Decompressed entries are limited to `max_bytes`. As Fluentd does, a malformed
+/// entry is skipped rather than rejecting the whole message, so its chunk is still acknowledged.
+pub fn decode_forward(message: Value, source: &str, max_bytes: usize) -> Result<ForwardMessage, String> {
+    let Value::Array(mut items) = message else {
+        return Err("forward message is not an array".to_string());
+    };
+    let tag = match items.first() {
+        Some(Value::String(tag)) => tag.as_str().ok_or("tag is not valid UTF-8")?.to_string(),
+        _ => return Err("forward message does not start with a tag".to_string()),
+    };
+
+    // Message mode : [tag, time, record, option?], the other modes : [tag, entries, option?]
+    let message_mode = !matches!(items.get(1), Some(Value::Array(_) | Value::String(_) | Value::Binary(_)));
+    let expected = if message_mode { 3..=4 } else { 2..=3 };
+    if !expected.contains(&items.len()) {
+        return Err(format!("forward message of {} items, expected {} to {}", items.len(), expected.start(), expected.end()));
+    }
+    let option = if items.len() == *expected.end() { items.pop() } else { None };
+    let (chunk, compressed) = parse_option(option)?;
+
+    let entries = if message_mode {
+        vec![Ok(Value::Array(items.split_off(1)))]
+    } else {
+        match items.pop() {
+            Some(Value::Array(entries)) => entries.into_iter().map(Ok).collect(),
+            Some(Value::String(packed)) => unpack_entries(&packed.into_bytes(), compressed, max_bytes)?,
+            Some(Value::Binary(packed)) => unpack_entries(&packed, compressed, max_bytes)?,
+            _ => return Err("forward message without entries".to_string()),
+        }
+    };
+
+    let mut records = Vec::with_capacity(entries.len());
+    let mut skipped = Vec::new();
+    for entry in entries {
+        match entry.and_then(|entry| record_from_entry(&tag, entry, source)) {
+            Ok(record) => records.push(record),
+            Err(e) => skipped.push(e),
+        }
+    }
+    Ok(ForwardMessage { records, skipped, chunk })
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Chunk id and gzip compression flag of the option map
+fn parse_option(option: Option<Value>) -> Result<(Option<String>, bool), String> {
+    let entries = match option {
+        None | Some(Value::Nil) => return Ok((None, false)),
+        Some(Value::Map(entries)) => entries,
+        Some(_) => return Err("forward option is not a map".to_string()),
+    };
+
+    let mut chunk = None;
+    let mut compressed = false;
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            (Some("chunk"), Value::String(id)) => chunk = id.into_str(),
+            (Some("compressed"), Value::String(method)) => match method.as_str() {
+                Some("gzip") => compressed = true,
+                Some("text") => {}
+                _ => return Err(format!("unsupported compression {}", method)),
+            },
+            _ => {}
+        }
+    }
+    Ok((chunk, compressed))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Entries of a PackedForward message : MessagePack `[time, record]` arrays back to back
+///
+/// Invalid MessagePack loses the position of the next entries : the entries before it are kept,
+/// followed by the decoding error.
+fn unpack_entries(packed: &[u8], compressed: bool, max_bytes: usize) -> Result<Vec<Result<Value, String>>, String> {
+    let mut inflated = Vec::new();
+    let mut data = packed;
+    if compressed {
+        // Bounded read, a small gzip stream may inflate to gigabytes
+        MultiGzDecoder::new(packed)
+            .take(max_bytes as u64 + 1)
+            .read_to_end(&mut inflated)
+            .map_err(|e| format!("invalid gzip entries - {}", e))?;
+        if inflated.len() > max_bytes {
+            return Err(format!("entries exceed the {} bytes limit once decompressed", max_bytes));
+        }
+        data = &inflated;
+    }
+
+    let mut entries = Vec::new();
+    while !data.is_empty() {
+        match rmpv::decode::read_value(&mut data) {
+            Ok(entry) => entries.push(Ok(entry)),
+            Err(e) => {
+                entries.push(Err(format!("invalid packed entry - {}", e)));
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Build a log record from one `[time, record]` entry
+fn record_from_entry(tag: &str, entry: Value, source: &str) -> Result<LogRecord, String> {
+    let Value::Array(entry) = entry else {
+        return Err("forward entry is not an array".to_string());
+    };
+    let mut entry = entry.into_iter();
+    let (Some(time), Some(Value::Map(fields)), None) = (entry.next(), entry.next(), entry.next()) else {
+        return Err("forward entry is not a [time, record] pair".to_string());
+    };
+
+    let mut record = LogRecord::new(source);
+    record.logger_name = tag.to_string();
+    record.timestamp = event_time(&time)?;
+    for (key, value) in fields {
+        apply_field(&mut record, map_key(key), value);
+    }
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// RFC 3339 timestamp of an event time : integer or float seconds, or an EventTime extension
+fn event_time(time: &Value) -> Result<String, String> {
+    let datetime = match time {
+        Value::Integer(seconds) => seconds.as_i64().and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+        Value::F64(seconds) => DateTime::from_timestamp(seconds.floor() as i64, (seconds.fract() * 1e9) as u32),
+        Value::Ext(EVENT_TIME_EXT_TYPE, data) if data.len() == 8 => {
+            let seconds = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+            let nanos = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+            DateTime::from_timestamp(seconds as i64, nanos)
+        }
+        _ => None,
+    };
+    datetime
+        .map(|datetime| datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        .ok_or_else(|| format!("invalid event time {}", time))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Set the schema field a record key maps to, or keep it as a typed attribute
+///
+/// A key is also kept as an attribute when its field is already set by an earlier key, or for a
+/// level that is not a level name.
+fn apply_field(record: &mut LogRecord, key: String, value: Value) {
+    let value = attribute_value(value);
+    let target = RECORD_FIELDS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, target)| *target);
+
+    let field = match target {
+        Some(Target::Level) => {
+            let level = match &value {
+                AttributeValue::String(name) => severity_text_level(name),
+                _ => Level::Notset,
+            };
+            if record.level == Level::Notset && level != Level::Notset {
+                record.level = level;
+                return;
+            }
+            None
+        }
+        Some(Target::Message) => Some(&mut record.message),
+        Some(Target::Hostname) => Some(&mut record.hostname),
+        Some(Target::Module) => Some(&mut record.module),
+        Some(Target::FileName) => Some(&mut record.filename),
+        Some(Target::FunctionName) => Some(&mut record.function_name),
+        Some(Target::LineNumber) => Some(&mut record.line_number),
+        Some(Target::PathName) => Some(&mut record.path_name),
+        Some(Target::ProcessId) => Some(&mut record.process_id),
+        Some(Target::ProcessName) => Some(&mut record.process_name),
+        Some(Target::ThreadId) => Some(&mut record.thread_id),
+        Some(Target::ThreadName) => Some(&mut record.thread_name),
+        Some(Target::ServiceName) => Some(&mut record.service_name),
+        Some(Target::StackTrace) => Some(&mut record.stack_trace),
+        None => None,
+    };
+    match field {
+        Some(field) if field.is_empty() => {
+            // Tailed container logs keep the line end
+            *field = value.to_string().trim_end_matches(['\r', '\n']).to_string();
+        }
+        _ => record.attributes.push((key, value)),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Typed attribute value, arrays and maps as JSON text
+fn attribute_value(value: Value) -> AttributeValue {
+    match value {
+        Value::String(text) => AttributeValue::String(String::from_utf8_lossy(text.as_bytes()).into_owned()),
+        Value::Binary(bytes) => AttributeValue::String(String::from_utf8_lossy(&bytes).into_owned()),
+        Value::Boolean(value) => AttributeValue::Bool(value),
+        Value::Integer(value) => match value.as_i64() {
+            Some(value) => AttributeValue::Int(value),
+            None => AttributeValue::String(value.to_string()),
+        },
+        Value::F32(value) => AttributeValue::Float(value as f64),
+        Value::F64(value) => AttributeValue::Float(value),
+        Value::Nil => AttributeValue::String(String::new()),
+        value => AttributeValue::String(json_value(value).to_string()),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// JSON form of a MessagePack value
+fn json_value(value: Value) -> JsonValue {
+    match value {
+        Value::Nil => JsonValue::Null,
+        Value::Boolean(value) => JsonValue::from(value),
+        Value::Integer(value) => match (value.as_i64(), value.as_u64()) {
+            (Some(value), _) => JsonValue::from(value),
+            (None, Some(value)) => JsonValue::from(value),
+            _ => JsonValue::Null,
+        },
+        Value::F32(value) => JsonValue::from(value),
+        Value::F64(value) => JsonValue::from(value),
+        Value::String(text) => JsonValue::from(String::from_utf8_lossy(text.as_bytes()).into_owned()),
+        Value::Binary(bytes) => JsonValue::from(String::from_utf8_lossy(&bytes).into_owned()),
+        Value::Array(values) => values.into_iter().map(json_value).collect(),
+        Value::Map(entries) => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (map_key(key), json_value(value)))
+                .collect::<Map<String, JsonValue>>(),
+        ),
+        Value::Ext(_, data) => JsonValue::from(String::from_utf8_lossy(&data).into_owned()),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Text of a map key, keys are normally strings
+fn map_key(key: Value) -> String {
+    match key {
+        Value::String(key) => String::from_utf8_lossy(key.as_bytes()).into_owned(),
+        key => key.to_string(),
+    }
+}
@@ -0,0 +1,215 @@
+//! GELF (Graylog Extended Log Format) message parsing
+//!
+//! Parses GELF 1.1 JSON payloads, zlib or gzip compressed or not, into log records. `level` is a
+//! syslog severity, `full_message` goes to the stack trace and `_` additional fields become typed
+//! attributes, except the usual Java appender fields that fill schema fields.
+
+use std::io::Read;
+
+use chrono::{DateTime, SecondsFormat};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde_json::Value;
+
+use crate::core::records::{AttributeValue, LogRecord};
+use crate::core::syslog::{severity_level, source_host};
+
+
+
+
+/// Schema field filled by a GELF field
+#[derive(Clone, Copy)]
+enum Target {
+    Hostname,
+    Message,
+    StackTrace,
+    Module,
+    LoggerName,
+    FunctionName,
+    LineNumber,
+    PathName,
+    ProcessId,
+    ProcessName,
+    ThreadId,
+    ThreadName,
+    ServiceName,
+}
+
+/// Standard fields mapped onto schema fields (`facility`, `file` and `line` are deprecated ones)
+const STANDARD_FIELDS: [(&str, Target); 6] = [
+    ("host", Target::Hostname),
+    ("short_message", Target::Message),
+    ("full_message", Target::StackTrace),
+    ("facility", Target::Module),
+    ("file", Target::PathName),
+    ("line", Target::LineNumber),
+];
+
+/// Additional fields, without their `_`, mapped onto schema fields : schema names, then the
+/// names used by logstash-gelf, log4j2 `GelfLayout` and gelfj appenders
+const ADDITIONAL_FIELDS: [(&str, Target); 17] = [
+    ("logger_name", Target::LoggerName),
+    ("module", Target::Module),
+    ("function_name", Target::FunctionName),
+    ("process_id", Target::ProcessId),
+    ("process_name", Target::ProcessName),
+    ("thread_id", Target::ThreadId),
+    ("thread_name", Target::ThreadName),
+    ("service_name", Target::ServiceName),
+    ("LoggerName", Target::LoggerName),
+    ("loggerName", Target::LoggerName),
+    ("logger", Target::LoggerName),
+    ("Thread", Target::ThreadName),
+    ("thread", Target::ThreadName),
+    ("SourceClassName", Target::Module),
+    ("SourceMethodName", Target::FunctionName),
+    ("SourceLineNumber", Target::LineNumber),
+    ("pid", Target::ProcessId),
+];
+
+/// Standard fields without a schema field
+const IGNORED_FIELDS: [&str; 2] = ["version", "_id"];
+
+//-----------------------------------------------------------------------------------------------
+
+/// Parse one GELF payload received from `source`, decompressed payloads are limited to `max_bytes`
+///
+/// The payload is zlib or gzip compressed when it starts with the matching magic bytes. A missing
+/// `host` falls back to the sender address, a missing `level` leaves the record `NOTSET`.
+pub fn parse_gelf(data: &[u8], source: &str, max_bytes: usize) -> Result<LogRecord, String> {
+    let json = decompress(data, max_bytes)?;
+    let Value::Object(fields) = serde_json::from_slice::<Value>(&json).map_err(|e| format!("invalid GELF JSON - {}", e))? else {
+        return Err("GELF message is not a JSON object".to_string());
+    };
+    if !matches!(fields.get("short_message"), Some(Value::String(_))) {
+        return Err("GELF message without short_message".to_string());
+    }
+
+    let mut record = LogRecord::new(source);
+    for (key, value) in fields {
+        match key.as_str() {
+            "timestamp" => record.timestamp = gelf_timestamp(&value)?,
+            "level" => record.level = severity_level(gelf_level(&value)?),
+            key if IGNORED_FIELDS.contains(&key) => {}
+            key => apply_field(&mut record, key, value),
+        }
+    }
+
+    if record.filename.is_empty() && !record.path_name.is_empty() {
+        record.filename = record.path_name.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+    }
+    if record.hostname.is_empty() {
+        record.hostname = source_host(source).to_string();
+    }
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Payload as JSON bytes : zlib (RFC 1950 header) or gzip magic, else taken as is
+fn decompress(data: &[u8], max_bytes: usize) -> Result<Vec<u8>, String> {
+    let reader: Box<dyn Read + '_> = match data {
+        [0x1f, 0x8b, ..] => Box::new(GzDecoder::new(data)),
+        [cmf, flg, ..] if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+            Box::new(ZlibDecoder::new(data))
+        }
+        _ => return Ok(data.to_vec()),
+    };
+
+    // Bounded read, a small compressed payload may inflate to gigabytes
+    let mut json = Vec::new();
+    reader
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("invalid compressed GELF payload - {}", e))?;
+    if json.len() > max_bytes {
+        return Err(format!("GELF message exceeds the {} bytes limit once decompressed", max_bytes));
+    }
+    Ok(json)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// RFC 3339 timestamp of the GELF UNIX timestamp, seconds with optional decimals
+fn gelf_timestamp(value: &Value) -> Result<String, String> {
+    let seconds = match value {
+        Value::Number(seconds) => seconds.as_f64(),
+        Value::String(seconds) => seconds.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    // Microsecond rounding, a f64 of current epoch seconds is not precise enough for nanoseconds
+    seconds
+        .filter(|seconds| seconds.is_finite())
+        .and_then(|seconds| DateTime::from_timestamp_micros((seconds * 1e6).round() as i64))
+        .map(|datetime| datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        .ok_or_else(|| format!("invalid GELF timestamp {}", value))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Syslog severity of the GELF level, 0 emergency .. 7 debug
+fn gelf_level(value: &Value) -> Result<u8, String> {
+    let level = match value {
+        Value::Number(level) => level.as_u64(),
+        Value::String(level) => level.trim().parse::<u64>().ok(),
+        _ => None,
+    };
+    level
+        .filter(|level| *level <= 7)
+        .map(|level| level as u8)
+        .ok_or_else(|| format!("invalid GELF level {}", value))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Set the schema field a GELF field maps to, or keep it as a typed attribute
+///
+/// Additional fields are kept without their `_` prefix. A field is also kept as an attribute when
+/// its schema field was already set by an earlier one.
+fn apply_field(record: &mut LogRecord, key: &str, value: Value) {
+    let (name, fields) = match key.strip_prefix('_') {
+        Some(name) => (name, &ADDITIONAL_FIELDS[..]),
+        None => (key, &STANDARD_FIELDS[..]),
+    };
+    let target = fields
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(_, target)| *target);
+    let value = attribute_value(value);
+
+    let field = match target {
+        Some(Target::Hostname) => Some(&mut record.hostname),
+        Some(Target::Message) => Some(&mut record.message),
+        Some(Target::StackTrace) => Some(&mut record.stack_trace),
+        Some(Target::Module) => Some(&mut record.module),
+        Some(Target::LoggerName) => Some(&mut record.logger_name),
+        Some(Target::FunctionName) => Some(&mut record.function_name),
+        Some(Target::LineNumber) => Some(&mut record.line_number),
+        Some(Target::PathName) => Some(&mut record.path_name),
+        Some(Target::ProcessId) => Some(&mut record.process_id),
+        Some(Target::ProcessName) => Some(&mut record.process_name),
+        Some(Target::ThreadId) => Some(&mut record.thread_id),
+        Some(Target::ThreadName) => Some(&mut record.thread_name),
+        Some(Target::ServiceName) => Some(&mut record.service_name),
+        None => None,
+    };
+    match field {
+        Some(field) if field.is_empty() => *field = value.to_string(),
+        _ => record.attributes.push((name.to_string(), value)),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Typed attribute value, arrays and objects as JSON text
+fn attribute_value(value: Value) -> AttributeValue {
+    match value {
+        Value::String(text) => AttributeValue::String(text),
+        Value::Bool(value) => AttributeValue::Bool(value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => AttributeValue::Int(value),
+            None => AttributeValue::Float(number.as_f64().unwrap_or_default()),
+        },
+        Value::Null => AttributeValue::String(String::new()),
+        value @ (Value::Array(_) | Value::Object(_)) => AttributeValue::String(value.to_string()),
+    }
+}
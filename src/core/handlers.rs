@@ -1,153 +1,339 @@
 //! Log message handling and processing
 //!
-//! Handles Cap'n Proto deserialization and message formatting.
+//! Handles Cap'n Proto / protobuf / JSON deserialization into typed log records, typed
+//! attributes included.
 
 use capnp::{message::ReaderOptions, serialize_packed};
-use tokio::sync::mpsc;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use serde_json::Value;
+
+use crate::core::records::{AttributeValue, LogRecord, LEVEL_STRINGS};
+use crate::core::writers::{PendingAck, WriterHandle};
+use crate::logger_capnp::logger_msg::{attribute, logger_msg, Level};
+use crate::network::grpc_server::log_service::attribute::Value as ProtoAttributeValue;
+use crate::network::grpc_server::log_service::Attribute as ProtoAttribute;
+use crate::network::grpc_server::log_service::LogRequest as ProtoLogRequest;
+
+
 
-mod logger_capnp {
-    include!("../logger_capnp/logger_msg.rs");
-}
 
+/// Nesting limit of Cap'n Proto messages, `LoggerMsg` is text fields plus a list of attributes
+const CAPNP_NESTING_LIMIT: i32 = 16;
 
-// In handlers.rs
-const LEVEL_STRINGS: [&str; 12] = [
-    "NOTSET", "DEBUG", "STREAM", "INFO", "LOGON", "LOGOUT", "TRADE", "SCHEDULE", "REPORT",
-    "WARNING", "ERROR", "CRITICAL",
-];
+//-----------------------------------------------------------------------------------------------
+
+/// Cap'n Proto reader limits matching the maximum TCP frame size
+///
+/// Packed encoding collapses runs of zero words, so the unpacked message may be larger than the
+/// frame : the traversal limit allows up to 8 times the frame size (one word per frame byte).
+pub fn capnp_reader_options(max_frame_bytes: usize) -> ReaderOptions {
+    let mut options = ReaderOptions::new();
+    options
+        .traversal_limit_in_words(Some(max_frame_bytes))
+        .nesting_limit(CAPNP_NESTING_LIMIT);
+    options
+}
 
 //-----------------------------------------------------------------------------------------------
 
-/// Handle incoming TCP client connection
+/// Handle incoming TCP client message
 pub async fn handle_tcp_message(
     data: Vec<u8>,
-    writer_tx: mpsc::Sender<String>,
-    sequence_counter: Arc<AtomicU64>,
-    _client_name: &str,
+    writer: &WriterHandle,
+    source: &str,
+    client: &str,
+    reader_options: ReaderOptions,
 ) -> Result<(), String> {
-    
-    // Perform Cap'n Proto deserialization in the current thread
-    let formatted_message = {
-        // All Cap'n Proto work happens in this block
-        let reader = serialize_packed::read_message(&mut &data[..], ReaderOptions::new())
-            .map_err(|e| format!("deserialization failed: {}", e))?;
-            
-        let log_message = reader
-            .get_root::<logger_capnp::logger_msg::Reader<'_>>()
-            .map_err(|e| format!("invalid message format: {}", e))?;
-
-        format_log_message_from_capnp(log_message)
-            .map_err(|e| format!("message formatting failed: {}", e))?
-    };
+
+    let record = decode_tcp_message(&data, source, client, reader_options)?;
 
     // Send to writer with sequence number (this part is Send safe)
-    let sequence = sequence_counter.fetch_add(1, Ordering::SeqCst);
-    let final_message = format!("{} {}", sequence, formatted_message);
-    
-    writer_tx
-        .send(final_message)
-        .await
-        .map_err(|e| format!("failed to queue message: {}", e))?;
-        
+    writer.send(record).await?;
+
     Ok(())
 }
 
 //-----------------------------------------------------------------------------------------------
 
-/// Handle gRPC log message
-pub async fn handle_grpc_message(
-    log_request: crate::network::grpc_server::InternalLogRequest,
-    writer_tx: mpsc::Sender<String>,
-    sequence_counter: Arc<AtomicU64>,
-) -> Result<(), String> {
-    
-    let formatted_message = format_log_message_from_grpc(log_request)
-        .map_err(|e| format!("message formatting failed: {}", e))?;
-
-    let sequence = sequence_counter.fetch_add(1, Ordering::SeqCst);
-    let final_message = format!("{} {}", sequence, formatted_message);
-    
-    writer_tx
-        .send(final_message)
+/// Decode a Cap'n Proto packed `LoggerMsg` into a log record
+pub fn decode_tcp_message(
+    data: &[u8],
+    source: &str,
+    client: &str,
+    reader_options: ReaderOptions,
+) -> Result<LogRecord, String> {
+
+    // All Cap'n Proto work happens here, the reader is not Send
+    let reader = serialize_packed::read_message(&mut &data[..], reader_options)
+        .map_err(|e| format!("deserialization failed: {}", e))?;
+
+    let log_message = reader
+        .get_root::<logger_msg::Reader<'_>>()
+        .map_err(|e| format!("invalid message format: {}", e))?;
+
+    let mut record = record_from_capnp(log_message, source)
+        .map_err(|e| format!("message conversion failed: {}", e))?;
+    record.client = client.to_string();
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Decode a gRPC log message into a record, errors are client errors (invalid level, attribute)
+pub fn decode_grpc_message(log_request: ProtoLogRequest, source: &str, client: &str) -> Result<LogRecord, String> {
+    let mut record = record_from_grpc(log_request, source)
+        .map_err(|e| format!("message conversion failed: {}", e))?;
+    record.client = client.to_string();
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Handle a decoded gRPC log message, errors are writer errors
+pub async fn handle_grpc_message(record: LogRecord, writer: &WriterHandle) -> Result<(), String> {
+
+    // The response is only returned once the record reached the stage the durability mode requires
+    writer
+        .send_acknowledged(record)
         .await
-        .map_err(|e| format!("failed to queue gRPC message: {}", e))?;
-        
+        .map_err(|e| format!("gRPC message rejected: {}", e))?;
+
     Ok(())
 }
 
 //-----------------------------------------------------------------------------------------------
-/// Unified log message formatting - used by both protocols
-fn format_log_message(
-    timestamp: &str,
-    hostname: &str,
-    logger_name: &str,
-    level: &str,
-    filename: &str,
-    function_name: &str,
-    line_number: &str,
-    message: &str,
-) -> String {
-
-    format!(
-        "{:<33} {:<12} {:<15} {:<8} {:<20} {:<25} {:<6} {}",
-        timestamp, 
-        truncate(hostname, 12), 
-        truncate(logger_name, 15), 
-        truncate(level, 8), 
-        truncate(filename, 20), 
-        truncate(function_name, 25), 
-        truncate(line_number, 6),
-        message
-    )
+
+/// Queue a gRPC log message without waiting, used by the batch and streaming RPCs
+///
+/// The returned acknowledgement resolves at the stage the durability mode requires.
+pub async fn queue_grpc_message(
+    log_request: ProtoLogRequest,
+    writer: &WriterHandle,
+    source: &str,
+    client: &str,
+) -> Result<PendingAck, String> {
+
+    let record = decode_grpc_message(log_request, source, client)?;
+    writer
+        .queue_acknowledged(record)
+        .await
+        .map_err(|e| format!("gRPC message rejected: {}", e))
 }
 
-// Helper to truncate long strings
-fn truncate(s: &str, max_len: usize) -> &str {
-    if s.len() > max_len {
-        &s[..max_len]
-    } else {
-        s
+//-----------------------------------------------------------------------------------------------
+
+/// Queue a JSON log object without waiting, used by the HTTP endpoint
+///
+/// The returned acknowledgement resolves at the stage the durability mode requires.
+pub async fn queue_json_message(
+    item: &Value,
+    writer: &WriterHandle,
+    source: &str,
+    client: &str,
+) -> Result<PendingAck, String> {
+
+    let log_request = decode_json_message(item)?;
+    let mut record = record_from_grpc(log_request, source)
+        .map_err(|e| format!("message conversion failed: {}", e))?;
+    record.client = client.to_string();
+
+    writer
+        .queue_acknowledged(record)
+        .await
+        .map_err(|e| format!("HTTP message rejected: {}", e))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Decode a JSON log object into a log request, the JSON field names are the `LogRequest` ones
+///
+/// `level` is a level name (case insensitive) or number, the text fields also accept numbers
+/// (`"line_number": 42`). `attributes` is an object of string, number or boolean values, as
+/// written in the JSON output. Unknown fields are rejected so a misspelt field is not silently lost.
+pub fn decode_json_message(item: &Value) -> Result<ProtoLogRequest, String> {
+    let object = item
+        .as_object()
+        .ok_or_else(|| "expected a JSON object".to_string())?;
+
+    let mut log_request = ProtoLogRequest::default();
+    for (name, value) in object {
+        if name == "level" {
+            log_request.level = json_level(value)?;
+            continue;
+        }
+        if name == "attributes" {
+            log_request.attributes = json_attributes(value)?;
+            continue;
+        }
+        let field = match name.as_str() {
+            "timestamp" => &mut log_request.timestamp,
+            "hostname" => &mut log_request.hostname,
+            "logger_name" => &mut log_request.logger_name,
+            "module" => &mut log_request.module,
+            "filename" => &mut log_request.filename,
+            "function_name" => &mut log_request.function_name,
+            "line_number" => &mut log_request.line_number,
+            "message" => &mut log_request.message,
+            "path_name" => &mut log_request.path_name,
+            "process_id" => &mut log_request.process_id,
+            "process_name" => &mut log_request.process_name,
+            "thread_id" => &mut log_request.thread_id,
+            "thread_name" => &mut log_request.thread_name,
+            "service_name" => &mut log_request.service_name,
+            "stack_trace" => &mut log_request.stack_trace,
+            _ => return Err(format!("unknown field {:?}", name)),
+        };
+        *field = match value {
+            Value::String(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+            Value::Null => String::new(),
+            _ => return Err(format!("field {:?} must be a string", name)),
+        };
     }
+    Ok(log_request)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Level of a JSON log object : a level name or number, NOTSET when null
+fn json_level(value: &Value) -> Result<i32, String> {
+    let level = match value {
+        Value::String(name) => LEVEL_STRINGS
+            .iter()
+            .position(|level| level.eq_ignore_ascii_case(name)),
+        Value::Number(number) => number
+            .as_u64()
+            .filter(|level| (*level as usize) < LEVEL_STRINGS.len())
+            .map(|level| level as usize),
+        Value::Null => Some(0),
+        _ => None,
+    };
+    level
+        .map(|level| level as i32)
+        .ok_or_else(|| format!("invalid level {}", value))
 }
 
 //-----------------------------------------------------------------------------------------------
 
-/// Format log message from Cap'n Proto using unified formatter
-fn format_log_message_from_capnp(
-    log_message: logger_capnp::logger_msg::Reader<'_>,
-) -> Result<String, Box<dyn std::error::Error>> {
-    
-    let timestamp = log_message.get_timestamp()?.to_str()?;
-    let hostname = log_message.get_hostname()?.to_str()?;
-    let logger_name = log_message.get_logger_name()?.to_str()?;
-    let level = LEVEL_STRINGS[log_message.get_level()? as usize];
-    let filename = log_message.get_filename()?.to_str()?;
-    let function_name = log_message.get_function_name()?.to_str()?;
-    let line_number = log_message.get_line_number()?.to_str()?;
-    let message = log_message.get_message()?.to_str()?;
-
-    Ok(format_log_message(
-        timestamp, hostname, logger_name, level, filename, function_name, line_number, message
-    ))
+/// Attributes of a JSON log object : `{"order_id": "A-17", "latency_ms": 12.5}`, none when null
+fn json_attributes(value: &Value) -> Result<Vec<ProtoAttribute>, String> {
+    let attributes = match value {
+        Value::Object(attributes) => attributes,
+        Value::Null => return Ok(Vec::new()),
+        _ => return Err("field \"attributes\" must be an object".to_string()),
+    };
+    attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(text) => ProtoAttributeValue::StringValue(text.clone()),
+                Value::Bool(value) => ProtoAttributeValue::BoolValue(*value),
+                Value::Number(number) => match number.as_i64() {
+                    Some(value) => ProtoAttributeValue::IntValue(value),
+                    None => ProtoAttributeValue::FloatValue(number.as_f64().unwrap_or_default()),
+                },
+                _ => return Err(format!("attribute {:?} must be a string, number or boolean", key)),
+            };
+            Ok(ProtoAttribute {
+                key: key.clone(),
+                value: Some(value),
+            })
+        })
+        .collect()
 }
 
 //-----------------------------------------------------------------------------------------------
 
-/// Format log message from gRPC using unified formatter
-fn format_log_message_from_grpc(
-    log_message: crate::network::grpc_server::InternalLogRequest,
-) -> Result<String, Box<dyn std::error::Error>> {
-    
-    Ok(format_log_message(
-        &log_message.timestamp,
-        &log_message.hostname,
-        &log_message.logger_name,
-        LEVEL_STRINGS[log_message.level as usize],
-        &log_message.filename,
-        &log_message.function_name,
-        &log_message.line_number,
-        &log_message.message,
-    ))
-}
\ No newline at end of file
+/// Build a log record from a Cap'n Proto message
+fn record_from_capnp(
+    log_message: logger_msg::Reader<'_>,
+    source: &str,
+) -> Result<LogRecord, Box<dyn std::error::Error>> {
+
+    let mut record = LogRecord::new(source);
+    record.timestamp = log_message.get_timestamp()?.to_string()?;
+    record.hostname = log_message.get_hostname()?.to_string()?;
+    record.logger_name = log_message.get_logger_name()?.to_string()?;
+    record.module = log_message.get_module()?.to_string()?;
+    record.level = log_message.get_level()?;
+    record.filename = log_message.get_filename()?.to_string()?;
+    record.function_name = log_message.get_function_name()?.to_string()?;
+    record.line_number = log_message.get_line_number()?.to_string()?;
+    record.message = log_message.get_message()?.to_string()?;
+    record.path_name = log_message.get_path_name()?.to_string()?;
+    record.process_id = log_message.get_process_id()?.to_string()?;
+    record.process_name = log_message.get_process_name()?.to_string()?;
+    record.thread_id = log_message.get_thread_id()?.to_string()?;
+    record.thread_name = log_message.get_thread_name()?.to_string()?;
+    record.service_name = log_message.get_service_name()?.to_string()?;
+    record.stack_trace = log_message.get_stack_trace()?.to_string()?;
+
+    // Absent from messages of older clients, read as an empty list
+    for attribute in log_message.get_attributes()? {
+        let key = attribute_key(attribute.get_key()?.to_string()?)?;
+        let value = match attribute.get_value().which()? {
+            attribute::value::StringValue(text) => AttributeValue::String(text?.to_string()?),
+            attribute::value::IntValue(value) => AttributeValue::Int(value),
+            attribute::value::FloatValue(value) => AttributeValue::Float(value),
+            attribute::value::BoolValue(value) => AttributeValue::Bool(value),
+        };
+        record.attributes.push((key, value));
+    }
+
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Build a log record from a gRPC request
+fn record_from_grpc(
+    log_request: ProtoLogRequest,
+    source: &str,
+) -> Result<LogRecord, Box<dyn std::error::Error>> {
+
+    let level = u16::try_from(log_request.level)
+        .ok()
+        .and_then(|level| Level::try_from(level).ok())
+        .ok_or_else(|| format!("invalid level {}", log_request.level))?;
+
+    let mut record = LogRecord::new(source);
+    record.timestamp = log_request.timestamp;
+    record.hostname = log_request.hostname;
+    record.logger_name = log_request.logger_name;
+    record.module = log_request.module;
+    record.level = level;
+    record.filename = log_request.filename;
+    record.function_name = log_request.function_name;
+    record.line_number = log_request.line_number;
+    record.message = log_request.message;
+    record.path_name = log_request.path_name;
+    record.process_id = log_request.process_id;
+    record.process_name = log_request.process_name;
+    record.thread_id = log_request.thread_id;
+    record.thread_name = log_request.thread_name;
+    record.service_name = log_request.service_name;
+    record.stack_trace = log_request.stack_trace;
+
+    for attribute in log_request.attributes {
+        let key = attribute_key(attribute.key)?;
+        let value = match attribute.value {
+            Some(ProtoAttributeValue::StringValue(text)) => AttributeValue::String(text),
+            Some(ProtoAttributeValue::IntValue(value)) => AttributeValue::Int(value),
+            Some(ProtoAttributeValue::FloatValue(value)) => AttributeValue::Float(value),
+            Some(ProtoAttributeValue::BoolValue(value)) => AttributeValue::Bool(value),
+            None => return Err(format!("attribute {:?} without a value", key).into()),
+        };
+        record.attributes.push((key, value));
+    }
+
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Attribute key, rejected when empty as it could not be rendered as `key=value`
+pub(crate) fn attribute_key(key: String) -> Result<String, String> {
+    if key.is_empty() {
+        return Err("attribute without a key".to_string());
+    }
+    Ok(key)
+}
@@ -2,4 +2,12 @@
 
 pub mod servers;
 pub mod handlers;
-pub mod writers;
\ No newline at end of file
+pub mod records;
+pub mod syslog;
+pub mod otlp;
+pub mod forward;
+pub mod gelf;
+pub mod formatters;
+pub mod compression;
+pub mod rotation;
+pub mod writers;
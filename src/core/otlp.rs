@@ -0,0 +1,244 @@
+//! OpenTelemetry (OTLP) log conversion
+//!
+//! Maps OTLP log records onto log records : severity onto the level, well known resource and
+//! semantic convention attributes onto the schema fields, every other attribute kept typed.
+
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{Map, Value};
+
+use crate::core::handlers::attribute_key;
+use crate::core::records::{AttributeValue, LogRecord, LEVEL_STRINGS};
+use crate::logger_capnp::logger_msg::Level;
+use crate::network::grpc_server::opentelemetry::proto::common::v1::{any_value, AnyValue, KeyValue};
+use crate::network::grpc_server::opentelemetry::proto::logs::v1::{LogRecord as OtlpLogRecord, ResourceLogs};
+
+
+
+
+/// Schema field filled by an OTLP attribute
+#[derive(Clone, Copy)]
+enum Target {
+    Hostname,
+    ServiceName,
+    ProcessId,
+    ProcessName,
+    ThreadId,
+    ThreadName,
+    PathName,
+    FunctionName,
+    LineNumber,
+    StackTrace,
+}
+
+/// Resource attributes (semantic conventions) mapped onto schema fields
+const RESOURCE_FIELDS: [(&str, Target); 4] = [
+    ("service.name", Target::ServiceName),
+    ("host.name", Target::Hostname),
+    ("process.pid", Target::ProcessId),
+    ("process.executable.name", Target::ProcessName),
+];
+
+/// Log record attributes (semantic conventions, current and deprecated names) mapped onto schema fields
+const RECORD_FIELDS: [(&str, Target); 9] = [
+    ("thread.id", Target::ThreadId),
+    ("thread.name", Target::ThreadName),
+    ("code.file.path", Target::PathName),
+    ("code.filepath", Target::PathName),
+    ("code.function.name", Target::FunctionName),
+    ("code.function", Target::FunctionName),
+    ("code.line.number", Target::LineNumber),
+    ("code.lineno", Target::LineNumber),
+    ("exception.stacktrace", Target::StackTrace),
+];
+
+//-----------------------------------------------------------------------------------------------
+
+/// Map an OTLP severity number (1 TRACE .. 24 FATAL4) onto a level, falling back on the severity text
+pub fn severity_level(severity_number: i32, severity_text: &str) -> Level {
+    match severity_number {
+        1..=8 => Level::Debug,
+        9..=12 => Level::Info,
+        13..=16 => Level::Warning,
+        17..=20 => Level::Error,
+        21..=24 => Level::Critical,
+        _ => severity_text_level(severity_text),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Level of a severity text : a level name, or a short name (TRACE, WARN, FATAL)
+pub fn severity_text_level(severity_text: &str) -> Level {
+    let name = severity_text.trim().to_ascii_uppercase();
+    let name = match name.as_str() {
+        "TRACE" => "DEBUG",
+        "WARN" => "WARNING",
+        "FATAL" => "CRITICAL",
+        name => name,
+    };
+    LEVEL_STRINGS
+        .iter()
+        .position(|level| *level == name)
+        .and_then(|level| Level::try_from(level as u16).ok())
+        .unwrap_or(Level::Notset)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Convert the log records of one `ResourceLogs`, received from `source`
+///
+/// The instrumentation scope name becomes the logger name. Resource attributes other than the
+/// mapped ones are kept on every record, before the record's own attributes. A record with an
+/// attribute without a key, its own or one of its resource, is rejected.
+pub fn records_from_otlp(resource_logs: ResourceLogs, source: &str) -> Vec<Result<LogRecord, String>> {
+    let mut resource = Ok(LogRecord::new(source));
+    for attribute in resource_logs.resource.map(|resource| resource.attributes).unwrap_or_default() {
+        resource = resource.and_then(|mut resource| {
+            apply_attribute(&mut resource, attribute, &RESOURCE_FIELDS)?;
+            Ok(resource)
+        });
+    }
+
+    let mut records = Vec::new();
+    for scope_logs in resource_logs.scope_logs {
+        let logger_name = scope_logs.scope.map(|scope| scope.name).unwrap_or_default();
+        for log_record in scope_logs.log_records {
+            let record = resource.clone().and_then(|mut record| {
+                record.logger_name = logger_name.clone();
+                fill_record(&mut record, log_record)?;
+                Ok(record)
+            });
+            records.push(record);
+        }
+    }
+    records
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Fill a record, pre-filled from its resource, with one OTLP log record
+fn fill_record(record: &mut LogRecord, log_record: OtlpLogRecord) -> Result<(), String> {
+    let time = match log_record.time_unix_nano {
+        0 => log_record.observed_time_unix_nano,
+        time => time,
+    };
+    if time != 0 {
+        record.timestamp = DateTime::from_timestamp_nanos(time as i64).to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    }
+    record.level = severity_level(log_record.severity_number, &log_record.severity_text);
+    record.message = log_record.body.map(any_value_text).unwrap_or_default();
+
+    for attribute in log_record.attributes {
+        apply_attribute(record, attribute, &RECORD_FIELDS)?;
+    }
+    if record.filename.is_empty() && !record.path_name.is_empty() {
+        record.filename = Path::new(&record.path_name)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+
+    // Trace context, when the log was emitted inside a span
+    if !log_record.trace_id.is_empty() {
+        record.attributes.push(("trace_id".to_string(), AttributeValue::String(hex(&log_record.trace_id))));
+    }
+    if !log_record.span_id.is_empty() {
+        record.attributes.push(("span_id".to_string(), AttributeValue::String(hex(&log_record.span_id))));
+    }
+    if !log_record.event_name.is_empty() {
+        record.attributes.push(("event.name".to_string(), AttributeValue::String(log_record.event_name)));
+    }
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Set the schema field an attribute maps to, or keep it as a typed attribute
+fn apply_attribute(record: &mut LogRecord, attribute: KeyValue, fields: &[(&str, Target)]) -> Result<(), String> {
+    let key = attribute_key(attribute.key)?;
+    let value = attribute.value.and_then(|value| value.value);
+    let target = fields
+        .iter()
+        .find(|(field_key, _)| *field_key == key)
+        .map(|(_, target)| *target);
+
+    let Some(target) = target else {
+        record.attributes.push((key, attribute_value(value)));
+        return Ok(());
+    };
+    let text = attribute_value(value).to_string();
+    let field = match target {
+        Target::Hostname => &mut record.hostname,
+        Target::ServiceName => &mut record.service_name,
+        Target::ProcessId => &mut record.process_id,
+        Target::ProcessName => &mut record.process_name,
+        Target::ThreadId => &mut record.thread_id,
+        Target::ThreadName => &mut record.thread_name,
+        Target::PathName => &mut record.path_name,
+        Target::FunctionName => &mut record.function_name,
+        Target::LineNumber => &mut record.line_number,
+        Target::StackTrace => &mut record.stack_trace,
+    };
+    *field = text;
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Typed attribute value, arrays and maps as JSON text, bytes as hex
+fn attribute_value(value: Option<any_value::Value>) -> AttributeValue {
+    match value {
+        Some(any_value::Value::StringValue(value)) => AttributeValue::String(value),
+        Some(any_value::Value::BoolValue(value)) => AttributeValue::Bool(value),
+        Some(any_value::Value::IntValue(value)) => AttributeValue::Int(value),
+        Some(any_value::Value::DoubleValue(value)) => AttributeValue::Float(value),
+        Some(any_value::Value::BytesValue(value)) => AttributeValue::String(hex(&value)),
+        Some(value) => AttributeValue::String(any_value_json(Some(value)).to_string()),
+        None => AttributeValue::String(String::new()),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Log body as message text, a structured body as JSON text
+fn any_value_text(body: AnyValue) -> String {
+    match body.value {
+        Some(any_value::Value::StringValue(text)) => text,
+        None => String::new(),
+        value => any_value_json(value).to_string(),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// JSON form of an OTLP value
+fn any_value_json(value: Option<any_value::Value>) -> Value {
+    match value {
+        Some(any_value::Value::StringValue(value)) => Value::from(value),
+        Some(any_value::Value::BoolValue(value)) => Value::from(value),
+        Some(any_value::Value::IntValue(value)) => Value::from(value),
+        Some(any_value::Value::DoubleValue(value)) => Value::from(value),
+        Some(any_value::Value::BytesValue(value)) => Value::from(hex(&value)),
+        Some(any_value::Value::ArrayValue(array)) => array
+            .values
+            .into_iter()
+            .map(|value| any_value_json(value.value))
+            .collect(),
+        Some(any_value::Value::KvlistValue(list)) => Value::Object(
+            list.values
+                .into_iter()
+                .map(|kv| (kv.key, any_value_json(kv.value.and_then(|value| value.value))))
+                .collect::<Map<String, Value>>(),
+        ),
+        None => Value::Null,
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Lower case hex of trace / span ids and bytes values
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,236 @@
+//! Structured log record
+//!
+//! Typed representation of a log message flowing from the handlers to the writer.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::logger_capnp::logger_msg::Level;
+
+
+
+
+/// Level names indexed by `Level` value
+pub const LEVEL_STRINGS: [&str; 12] = [
+    "NOTSET", "DEBUG", "STREAM", "INFO", "LOGON", "LOGOUT", "TRADE", "SCHEDULE", "REPORT",
+    "WARNING", "ERROR", "CRITICAL",
+];
+
+//-----------------------------------------------------------------------------------------------
+
+/// Log record carrying every schema field plus server side metadata
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    // recorded by log_server
+    pub sequence: u64,
+    pub received_at: DateTime<Utc>,
+    pub source: String,
+    /// Client identity of the authentication token, empty without authentication
+    pub client: String,
+
+    // schema fields (LoggerMsg / LogRequest)
+    pub timestamp: String,
+    pub hostname: String,
+    pub logger_name: String,
+    pub module: String,
+    pub level: Level,
+    pub filename: String,
+    pub function_name: String,
+    pub line_number: String,
+    pub message: String,
+    pub path_name: String,
+    pub process_id: String,
+    pub process_name: String,
+    pub thread_id: String,
+    pub thread_name: String,
+    pub service_name: String,
+    pub stack_trace: String,
+
+    /// Structured key / values beyond the schema fields, in received order
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl LogRecord {
+    /// Create an empty record received now from `source`, sequence is assigned by the writer handle
+    pub fn new(source: &str) -> Self {
+        Self {
+            sequence: 0,
+            received_at: Utc::now(),
+            source: source.to_string(),
+            client: String::new(),
+            timestamp: String::new(),
+            hostname: String::new(),
+            logger_name: String::new(),
+            module: String::new(),
+            level: Level::Notset,
+            filename: String::new(),
+            function_name: String::new(),
+            line_number: String::new(),
+            message: String::new(),
+            path_name: String::new(),
+            process_id: String::new(),
+            process_name: String::new(),
+            thread_id: String::new(),
+            thread_name: String::new(),
+            service_name: String::new(),
+            stack_trace: String::new(),
+            attributes: Vec::new(),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Level name as written in the output
+    pub fn level_name(&self) -> &'static str {
+        LEVEL_STRINGS[self.level as usize]
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Typed value of a structured attribute
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl fmt::Display for AttributeValue {
+    /// Value as text, strings unquoted
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeValue::String(value) => f.write_str(value),
+            AttributeValue::Int(value) => write!(f, "{}", value),
+            AttributeValue::Float(value) => write!(f, "{}", value),
+            AttributeValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Addressable field of a log record, used to configure output layouts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogField {
+    Sequence,
+    ReceivedAt,
+    Source,
+    Client,
+    Timestamp,
+    Hostname,
+    LoggerName,
+    Module,
+    Level,
+    Filename,
+    FunctionName,
+    LineNumber,
+    Message,
+    PathName,
+    ProcessId,
+    ProcessName,
+    ThreadId,
+    ThreadName,
+    ServiceName,
+    StackTrace,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl LogField {
+    /// Every field, in record order
+    pub const ALL: [LogField; 20] = [
+        LogField::Sequence, LogField::ReceivedAt, LogField::Source, LogField::Client,
+        LogField::Timestamp, LogField::Hostname, LogField::LoggerName, LogField::Module,
+        LogField::Level, LogField::Filename, LogField::FunctionName, LogField::LineNumber,
+        LogField::Message, LogField::PathName, LogField::ProcessId, LogField::ProcessName,
+        LogField::ThreadId, LogField::ThreadName, LogField::ServiceName, LogField::StackTrace,
+    ];
+
+    /// Schema fields not part of the fixed text columns
+    pub const EXTRAS: [LogField; 8] = [
+        LogField::Module, LogField::PathName, LogField::ProcessId, LogField::ProcessName,
+        LogField::ThreadId, LogField::ThreadName, LogField::ServiceName, LogField::StackTrace,
+    ];
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Field name as used in configuration and structured outputs
+    pub fn name(self) -> &'static str {
+        match self {
+            LogField::Sequence => "seq",
+            LogField::ReceivedAt => "received_at",
+            LogField::Source => "source",
+            LogField::Client => "client",
+            LogField::Timestamp => "timestamp",
+            LogField::Hostname => "hostname",
+            LogField::LoggerName => "logger_name",
+            LogField::Module => "module",
+            LogField::Level => "level",
+            LogField::Filename => "filename",
+            LogField::FunctionName => "function_name",
+            LogField::LineNumber => "line_number",
+            LogField::Message => "message",
+            LogField::PathName => "path_name",
+            LogField::ProcessId => "process_id",
+            LogField::ProcessName => "process_name",
+            LogField::ThreadId => "thread_id",
+            LogField::ThreadName => "thread_name",
+            LogField::ServiceName => "service_name",
+            LogField::StackTrace => "stack_trace",
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Look up a field by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Parse a comma separated field list, an empty string selects no field
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Self::from_name(name).ok_or_else(|| format!("unknown field '{}'", name)))
+            .collect()
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Value of the field in `record`, as text
+    pub fn value(self, record: &LogRecord) -> Cow<'_, str> {
+        match self {
+            LogField::Sequence => Cow::Owned(record.sequence.to_string()),
+            LogField::ReceivedAt => Cow::Owned(record.received_at.to_rfc3339()),
+            LogField::Source => Cow::Borrowed(&record.source),
+            LogField::Client => Cow::Borrowed(&record.client),
+            LogField::Timestamp => Cow::Borrowed(&record.timestamp),
+            LogField::Hostname => Cow::Borrowed(&record.hostname),
+            LogField::LoggerName => Cow::Borrowed(&record.logger_name),
+            LogField::Module => Cow::Borrowed(&record.module),
+            LogField::Level => Cow::Borrowed(record.level_name()),
+            LogField::Filename => Cow::Borrowed(&record.filename),
+            LogField::FunctionName => Cow::Borrowed(&record.function_name),
+            LogField::LineNumber => Cow::Borrowed(&record.line_number),
+            LogField::Message => Cow::Borrowed(&record.message),
+            LogField::PathName => Cow::Borrowed(&record.path_name),
+            LogField::ProcessId => Cow::Borrowed(&record.process_id),
+            LogField::ProcessName => Cow::Borrowed(&record.process_name),
+            LogField::ThreadId => Cow::Borrowed(&record.thread_id),
+            LogField::ThreadName => Cow::Borrowed(&record.thread_name),
+            LogField::ServiceName => Cow::Borrowed(&record.service_name),
+            LogField::StackTrace => Cow::Borrowed(&record.stack_trace),
+        }
+    }
+}
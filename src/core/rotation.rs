@@ -0,0 +1,375 @@
+//! Log file rotation policy
+//!
+//! Decides when the current log file rotates (size and/or time boundaries) and how rotated files
+//! are named : numbered backups for size-only rotation, period names for time-based rotation.
+//! Rotated files are optionally compressed, then retention limits are enforced, in a background task.
+
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
+use tokio::fs;
+use tokio::task::JoinHandle;
+
+use crate::core::compression::{Compression, PART_SUFFIX};
+use crate::core::writers::WriterConfig;
+
+
+
+
+/// Time boundary at which the log file rotates (UTC)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationInterval {
+    /// Every N minutes, aligned on multiples of N since the epoch
+    Minutes(u32),
+    /// At the start of every hour
+    Hourly,
+    /// At UTC midnight
+    Daily,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl RotationInterval {
+    /// Parse `daily`, `hourly` or `<N>m` (e.g. `15m`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "daily" => Some(RotationInterval::Daily),
+            "hourly" => Some(RotationInterval::Hourly),
+            _ => {
+                let minutes = name.strip_suffix('m')?.parse::<u32>().ok()?;
+                (minutes > 0).then_some(RotationInterval::Minutes(minutes))
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Length of one period
+    fn duration(self) -> ChronoDuration {
+        match self {
+            RotationInterval::Minutes(minutes) => ChronoDuration::minutes(minutes as i64),
+            RotationInterval::Hourly => ChronoDuration::hours(1),
+            RotationInterval::Daily => ChronoDuration::days(1),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Start of the period containing `time`
+    fn period_start(self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time.duration_trunc(self.duration()).unwrap_or(time)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Label of the period starting at `start`, used in rotated file names
+    fn label(self, start: DateTime<Utc>) -> String {
+        match self {
+            RotationInterval::Minutes(_) => start.format("%Y-%m-%dT%H-%M").to_string(),
+            RotationInterval::Hourly => start.format("%Y-%m-%dT%H").to_string(),
+            RotationInterval::Daily => start.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Rotation state of the current log file
+pub struct Rotator {
+    base_path: PathBuf,
+    interval: Option<RotationInterval>,
+    max_file_bytes: u64,
+    backup_count: usize,
+    compression: Option<Compression>,
+    retention: RetentionPolicy,
+    period_start: DateTime<Utc>,
+    pending_task: Option<JoinHandle<()>>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Rotator {
+    /// Create rotation state, `file_modified` dates the data already in the current file
+    pub fn new(base_path: &Path, config: &WriterConfig, file_modified: Option<DateTime<Utc>>) -> Self {
+        let since = file_modified.unwrap_or_else(Utc::now);
+        let interval = config.rotation_interval;
+        Self {
+            base_path: base_path.to_path_buf(),
+            interval,
+            max_file_bytes: config.max_file_bytes,
+            backup_count: config.backup_count,
+            compression: config.compression,
+            retention: RetentionPolicy {
+                // numbered backups are already bounded by the shift
+                max_files: interval.map(|_| config.backup_count),
+                max_age: config.retention_max_age,
+                max_total_bytes: config.retention_max_bytes,
+            },
+            period_start: interval.map_or(since, |interval| interval.period_start(since)),
+            pending_task: None,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Next time boundary, None for size-only rotation
+    pub fn next_boundary(&self) -> Option<DateTime<Utc>> {
+        self.interval.map(|interval| self.period_start + interval.duration())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// True when the current period is over
+    pub fn time_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_boundary().is_some_and(|boundary| now >= boundary)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// True when the file reached the size limit (0 disables size rotation)
+    pub fn size_due(&self, file_size: u64) -> bool {
+        self.max_file_bytes > 0 && file_size >= self.max_file_bytes
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Start a new period containing `now` without rotating (current file is empty)
+    pub fn start_period(&mut self, now: DateTime<Utc>) {
+        if let Some(interval) = self.interval {
+            self.period_start = interval.period_start(now);
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Move the current file aside, then compress it and enforce retention in the background
+    ///
+    /// The caller must have flushed the current file and reopens a fresh one afterwards.
+    /// Returns the rotated (uncompressed) file path, None when `backup_count` is 0 and the file
+    /// was deleted instead.
+    pub async fn rotate(&mut self, now: DateTime<Utc>) -> tokio::io::Result<Option<PathBuf>> {
+        // Backups are renamed below : the previous background task must not race with them
+        self.wait_background().await;
+
+        // No backup kept, whether rotation is by size or by time
+        if self.backup_count == 0 {
+            fs::remove_file(&self.base_path).await?;
+            self.start_period(now);
+            return Ok(None);
+        }
+
+        let rotated_path = match self.interval {
+            Some(interval) => {
+                let path = self.next_period_path(&interval.label(self.period_start)).await;
+                fs::rename(&self.base_path, &path).await?;
+                path
+            }
+            None => self.shift_numbered_backups().await?,
+        };
+        self.start_period(now);
+
+        let compression = self.compression;
+        let retention = self.retention;
+        let base_path = self.base_path.clone();
+        let path = rotated_path.clone();
+        self.pending_task = Some(tokio::spawn(async move {
+            if let Some(compression) = compression {
+                let compressed = tokio::task::spawn_blocking(move || compression.compress_file(&path)).await;
+                if let Ok(Err(e)) = compressed {
+                    eprintln!("Log writer : failed to compress rotated file - {}", e);
+                }
+            }
+            if let Err(e) = retention.enforce(&base_path).await {
+                eprintln!("Log writer : failed to enforce retention - {}", e);
+            }
+        }));
+        Ok(Some(rotated_path))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Enforce retention limits now, used on startup
+    pub async fn enforce_retention(&self) -> tokio::io::Result<()> {
+        self.retention.enforce(&self.base_path).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Wait for the background compression / retention of the last rotation, if any
+    pub async fn wait_background(&mut self) {
+        if let Some(pending) = self.pending_task.take() {
+            let _ = pending.await;
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // First free `<stem>.<label>[.N].log` path for the rotated period
+    //
+    // A name is taken by the plain file, its archives or their `.part` files : an earlier rotation
+    // of the same period may already be compressed, or still compressing.
+    async fn next_period_path(&self, label: &str) -> PathBuf {
+        let stem = self.base_path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let mut name = format!("{}.{}.log", stem, label);
+        let mut index = 1;
+        while Self::name_taken(&self.base_path, &name).await {
+            name = format!("{}.{}.{}.log", stem, label, index);
+            index += 1;
+        }
+        self.base_path.with_file_name(name)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // True when `name` or one of its compressed or `.part` variants exists next to `base_path`
+    async fn name_taken(base_path: &Path, name: &str) -> bool {
+        let suffixes = std::iter::once("").chain(Compression::ALL.map(Compression::suffix));
+        for suffix in suffixes {
+            for part in ["", PART_SUFFIX] {
+                let path = base_path.with_file_name(format!("{}{}{}", name, suffix, part));
+                if fs::try_exists(&path).await.unwrap_or(false) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // Size-only rotation : shift `.log.N[.gz|.zst]` backups and move the current file to `.log.0`
+    //
+    // Keeps `.log.0` through `.log.<backup_count - 1>`, the oldest backup is deleted to make room
+    // whatever its compression.
+    async fn shift_numbered_backups(&self) -> tokio::io::Result<PathBuf> {
+        let suffixes = std::iter::once("").chain(Compression::ALL.map(Compression::suffix));
+        for suffix in suffixes.clone() {
+            let oldest_path = self.base_path.with_extension(format!("log.{}{}", self.backup_count - 1, suffix));
+            if fs::metadata(&oldest_path).await.is_ok() {
+                fs::remove_file(&oldest_path).await?;
+            }
+        }
+        for suffix in suffixes {
+            for i in (1..self.backup_count).rev() {
+                let old_path = self.base_path.with_extension(format!("log.{}{}", i - 1, suffix));
+                let new_path = self.base_path.with_extension(format!("log.{}{}", i, suffix));
+
+                if fs::metadata(&old_path).await.is_ok() {
+                    fs::rename(&old_path, &new_path).await?;
+                }
+            }
+        }
+
+        let rotated_path = self.base_path.with_extension("log.0");
+        fs::rename(&self.base_path, &rotated_path).await?;
+        Ok(rotated_path)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Limits on rotated files, the current log file is never deleted
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetentionPolicy {
+    /// Keep at most this many rotated files
+    pub max_files: Option<usize>,
+    /// Delete rotated files last modified longer ago than this
+    pub max_age: Option<Duration>,
+    /// Keep the current file plus rotated files under this many bytes
+    pub max_total_bytes: Option<u64>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl RetentionPolicy {
+    /// Delete rotated files of the log at `base_path`, oldest first, until every limit holds
+    pub async fn enforce(&self, base_path: &Path) -> tokio::io::Result<()> {
+        let mut files = list_rotated_files(base_path).await?;
+        let now = SystemTime::now();
+        let current_size = fs::metadata(base_path).await.map_or(0, |metadata| metadata.len());
+        let mut total_size = current_size + files.iter().map(|file| file.len).sum::<u64>();
+
+        // `files` is sorted newest first : pop from the back to delete oldest first
+        while let Some(oldest) = files.last() {
+            let too_many = self.max_files.is_some_and(|max_files| files.len() > max_files);
+            let too_old = self.max_age.is_some_and(|max_age| {
+                now.duration_since(oldest.modified).is_ok_and(|age| age > max_age)
+            });
+            let too_big = self.max_total_bytes.is_some_and(|max_bytes| total_size > max_bytes);
+            if !(too_many || too_old || too_big) {
+                break;
+            }
+
+            fs::remove_file(&oldest.path).await?;
+            println!("Log writer : retention removed {}", oldest.path.display());
+            total_size -= oldest.len;
+            files.pop();
+        }
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Rotated log file found on disk
+#[derive(Clone, Debug)]
+pub struct RotatedFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub len: u64,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Rotated files of the log at `base_path`, most recently modified first
+///
+/// Only names the rotation gives (`<stem>.log.N`, `<stem>.<period>[.N].log`, optionally compressed)
+/// are listed : other files sharing the directory are never touched by retention. `.part` files
+/// are archives still being written (or left by an interrupted compression whose source file is
+/// still there) : they are not rotated files.
+pub async fn list_rotated_files(base_path: &Path) -> tokio::io::Result<Vec<RotatedFile>> {
+    let (Some(dir), Some(stem)) = (base_path.parent(), base_path.file_stem()) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.", stem.to_string_lossy());
+    let base_name = base_path.file_name();
+
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let text = name.to_string_lossy();
+        let rotated = text.strip_prefix(&prefix).is_some_and(is_rotated_name);
+        if Some(name.as_os_str()) == base_name || !rotated {
+            continue;
+        }
+        let metadata = entry.metadata().await?;
+        if metadata.is_file() {
+            files.push(RotatedFile {
+                path: entry.path(),
+                modified: metadata.modified()?,
+                len: metadata.len(),
+            });
+        }
+    }
+    files.sort_by_key(|file| Reverse(file.modified));
+    Ok(files)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// True for the part of a rotated file name after `<stem>.` : `log.N` or `<period>[.N].log`,
+// optionally followed by a compression suffix (a `.part` suffix does not match)
+fn is_rotated_name(rest: &str) -> bool {
+    let rest = Compression::ALL
+        .into_iter()
+        .find_map(|codec| rest.strip_suffix(codec.suffix()))
+        .unwrap_or(rest);
+    let numbered = rest
+        .strip_prefix("log.")
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|byte| byte.is_ascii_digit()));
+    let period = rest.ends_with(".log") && rest.starts_with(|first: char| first.is_ascii_digit());
+    numbered || period
+}
@@ -2,12 +2,22 @@
 //!
 //! Coordinates TCP and gRPC servers with shared file writer.
 
+use std::future::Future;
 use std::sync::Arc;
 
+use tokio::task::JoinHandle;
+
 use crate::network::tcp_server::TcpServer;
 use crate::network::grpc_server::GrpcServer;
-use crate::core::writers::LogWriter;
+use crate::network::unix_server::UnixServer;
+use crate::network::udp_server::UdpServer;
+use crate::network::syslog_server::SyslogServer;
+use crate::network::http_server::HttpServer;
+use crate::network::forward_server::ForwardServer;
+use crate::network::gelf_server::GelfServer;
+use crate::core::writers::{LogWriter, WriterConfig, WriterHandle};
 use crate::common::config::ServerConfig;
+use crate::common::stats::ServerStats;
 
 
 
@@ -16,7 +26,8 @@ use crate::common::config::ServerConfig;
 pub struct LogServer {
     name: String,
     config: ServerConfig,
-    writer: Arc<LogWriter>,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
     tcp_only: bool,
 }
 
@@ -24,59 +35,86 @@ pub struct LogServer {
 
 impl LogServer {
     /// Create new log server instance
-    pub async fn new(name: &str, host: &str, port: u16, grpc_port: u16, tcp_only: bool) -> Result<Self, Box<dyn std::error::Error>> {
-        let config = ServerConfig::new(name, host, port, grpc_port);
+    pub async fn new(config: ServerConfig, writer_config: WriterConfig, tcp_only: bool) -> Result<Self, Box<dyn std::error::Error>> {
         
         // Create log directory
         crate::utils::create_log_folder("logs")?;
         
-        // Initialize writer
-        let writer = Arc::new(LogWriter::new().await?);
+        // Initialize the single writer pipeline shared by every protocol
+        let writer = LogWriter::new(writer_config).await?.start_writer_task();
         
         Ok(Self {
-            name: name.to_string(),
+            name: config.name.clone(),
             config,
             writer,
+            stats: Arc::new(ServerStats::default()),
             tcp_only,
         })
     }
     
     //-----------------------------------------------------------------------------------------------
     
+    /// Ingestion counters shared by the protocol servers
+    pub fn stats(&self) -> Arc<ServerStats> {
+        self.stats.clone()
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
     /// Run the log server with all components
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         println!("{} : starting server components", self.name);
         
-        
-        // Start TCP server (always)
-        let tcp_server = TcpServer::new(&self.config, self.writer.clone());
-        let tcp_handle = tokio::spawn(async move {
-            if let Err(e) = tcp_server.run().await {
-                eprintln!("TCP server error: {}", e);
-            }
-        });
-        
-        // Conditionally start gRPC server
-        let grpc_handle = if !self.tcp_only {
-            let grpc_server = GrpcServer::new(&self.config, self.writer.clone());
-            Some(tokio::spawn(async move {
-                if let Err(e) = grpc_server.run().await {
-                    eprintln!("gRPC server error: {}", e);
-                }
-            }))
-        } else {
-            None
-        };
+        // TCP always, every other listener when configured
+        let config = &self.config;
+        let handles: Vec<JoinHandle<()>> = [
+            self.spawn_server(true, "TCP", TcpServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.unix_socket.is_some(), "Unix socket", UnixServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.udp_port.is_some(), "UDP", UdpServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.syslog_port.is_some(), "syslog", SyslogServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.http_port.is_some(), "HTTP", HttpServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.forward_port.is_some(), "forward", ForwardServer::new, |server| async move { server.run().await }),
+            self.spawn_server(config.gelf_port.is_some(), "GELF", GelfServer::new, |server| async move { server.run().await }),
+            self.spawn_server(!self.tcp_only, "gRPC", GrpcServer::new, |server| async move { server.run().await }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
         
         println!("{} : all server components started", self.name);
         
         // Wait for servers to complete
-        let _ = tcp_handle.await;
-        
-        if let Some(grpc_handle) = grpc_handle {
-        let _ = grpc_handle.await;
+        for handle in handles {
+            let _ = handle.await;
         }
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Spawn one protocol server when `enabled`, reporting the error it stops with
+    ///
+    /// Every server is built from the shared configuration, writer and counters with `new`, then
+    /// driven by `run`.
+    fn spawn_server<S, F>(
+        &self,
+        enabled: bool,
+        kind: &'static str,
+        new: fn(&ServerConfig, WriterHandle, Arc<ServerStats>) -> S,
+        run: impl FnOnce(S) -> F,
+    ) -> Option<JoinHandle<()>>
+    where
+        F: Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + 'static,
+    {
+        if !enabled {
+            return None;
+        }
+        let server = run(new(&self.config, self.writer.clone(), self.stats.clone()));
+        Some(tokio::spawn(async move {
+            if let Err(e) = server.await {
+                eprintln!("{} server error: {}", kind, e);
+            }
+        }))
+    }
+}
@@ -0,0 +1,220 @@
+//! Syslog message parsing
+//!
+//! RFC 5424 and legacy RFC 3164 (BSD) messages are parsed into log records. The parser is lenient
+//! like most syslog daemons : missing parts are left empty rather than rejecting the message.
+
+use chrono::{Datelike, Duration, NaiveDateTime, Utc};
+
+use crate::core::records::LogRecord;
+use crate::logger_capnp::logger_msg::Level;
+
+
+
+
+/// Highest valid PRI value (facility 23, severity 7)
+const MAX_PRIORITY: u32 = 191;
+
+/// PRI assumed when a message has none (user.notice, RFC 3164 section 4.3.3)
+const DEFAULT_PRIORITY: u32 = 13;
+
+/// Facility names indexed by facility code
+const FACILITY_NAMES: [&str; 24] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron", "authpriv",
+    "ftp", "ntp", "audit", "alert", "clock", "local0", "local1", "local2", "local3", "local4",
+    "local5", "local6", "local7",
+];
+
+/// RFC 5424 NILVALUE
+const NIL: &str = "-";
+
+//-----------------------------------------------------------------------------------------------
+
+/// Map a syslog severity (0 emergency .. 7 debug) onto a level
+pub fn severity_level(severity: u8) -> Level {
+    match severity {
+        0..=2 => Level::Critical,
+        3 => Level::Error,
+        4 => Level::Warning,
+        5 | 6 => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Name of a syslog facility code
+pub fn facility_name(facility: u8) -> &'static str {
+    FACILITY_NAMES.get(facility as usize).copied().unwrap_or("unknown")
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Parse one syslog message received from `source`
+///
+/// Severity maps onto the level, the facility name goes to `module`. APP-NAME / TAG fill
+/// `logger_name` and `process_name`, PROCID / pid `process_id`. RFC 5424 MSGID and
+/// STRUCTURED-DATA are kept in front of the message text.
+pub fn parse_syslog(data: &[u8], source: &str) -> Result<LogRecord, String> {
+    let text = String::from_utf8_lossy(data);
+    let text = text.trim_end_matches(['\r', '\n', '\0']);
+    if text.is_empty() {
+        return Err("empty syslog message".to_string());
+    }
+
+    let (priority, rest) = parse_priority(text)?;
+    let mut record = LogRecord::new(source);
+    record.level = severity_level((priority % 8) as u8);
+    record.module = facility_name((priority / 8) as u8).to_string();
+
+    // RFC 5424 messages carry a version right after the PRI
+    match rest.strip_prefix("1 ") {
+        Some(rest) => parse_rfc5424(rest, &mut record),
+        None => parse_rfc3164(rest, &mut record),
+    }
+
+    // Local senders often omit the hostname
+    if record.hostname.is_empty() {
+        record.hostname = source_host(source).to_string();
+    }
+    Ok(record)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Split `<PRI>` off the message, a message without PRI gets the default one
+fn parse_priority(text: &str) -> Result<(u32, &str), String> {
+    let Some(rest) = text.strip_prefix('<') else {
+        return Ok((DEFAULT_PRIORITY, text));
+    };
+    let end = rest
+        .find('>')
+        .filter(|end| (1..=3).contains(end))
+        .ok_or_else(|| "invalid syslog PRI".to_string())?;
+    let priority = rest[..end]
+        .parse::<u32>()
+        .ok()
+        .filter(|priority| *priority <= MAX_PRIORITY)
+        .ok_or_else(|| format!("invalid syslog PRI <{}>", &rest[..end]))?;
+    Ok((priority, &rest[end + 1..]))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// RFC 5424 : TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]
+fn parse_rfc5424(rest: &str, record: &mut LogRecord) {
+    let mut parts = rest.splitn(6, ' ');
+    let mut next = || parts.next().filter(|part| *part != NIL).unwrap_or("").to_string();
+    record.timestamp = next();
+    record.hostname = next();
+    record.logger_name = next();
+    record.process_name = record.logger_name.clone();
+    record.process_id = next();
+    let message_id = next();
+    let remainder = parts.next().unwrap_or("");
+
+    let (structured_data, message) = split_structured_data(remainder);
+    // The message may start with a UTF-8 BOM
+    let message = message.trim_start_matches('\u{feff}');
+    record.message = [message_id.as_str(), structured_data, message]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Split the STRUCTURED-DATA elements off the message, nil structured data is dropped
+fn split_structured_data(remainder: &str) -> (&str, &str) {
+    if let Some(message) = remainder.strip_prefix(NIL) {
+        return ("", message.strip_prefix(' ').unwrap_or(message));
+    }
+    if !remainder.starts_with('[') {
+        return ("", remainder);
+    }
+
+    // Elements end at a ']' outside a quoted PARAM-VALUE, where '\' escapes '"', '\' and ']'
+    let bytes = remainder.as_bytes();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut end = remainder.len();
+    for (index, &byte) in bytes.iter().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match byte {
+            b'\\' if in_quotes => escaped = true,
+            b'"' => in_quotes = !in_quotes,
+            b']' if !in_quotes && bytes.get(index + 1) != Some(&b'[') => {
+                end = index + 1;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let message = &remainder[end..];
+    (&remainder[..end], message.strip_prefix(' ').unwrap_or(message))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// RFC 3164 : Mmm dd hh:mm:ss HOSTNAME TAG[pid]: MSG, every part optional
+fn parse_rfc3164(rest: &str, record: &mut LogRecord) {
+    let mut rest = rest;
+    if let Some(timestamp) = rest.get(..15).and_then(parse_bsd_timestamp) {
+        record.timestamp = timestamp;
+        rest = rest[15..].trim_start_matches(' ');
+
+        // A hostname follows the timestamp, unless the sender went straight to the tag
+        if let Some((hostname, after)) = rest.split_once(' ') {
+            if !hostname.ends_with(':') && !hostname.contains('[') {
+                record.hostname = hostname.to_string();
+                rest = after;
+            }
+        }
+    }
+
+    // TAG : up to 32 alphanumeric chars, then '[pid]' and / or ':'
+    let tag_end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_./".contains(c)))
+        .unwrap_or(rest.len());
+    let after_tag = &rest[tag_end..];
+    if tag_end > 0 && tag_end <= 32 && (after_tag.starts_with('[') || after_tag.starts_with(':')) {
+        record.logger_name = rest[..tag_end].to_string();
+        record.process_name = record.logger_name.clone();
+        let mut after_tag = after_tag;
+        if let Some((pid, after)) = after_tag.strip_prefix('[').and_then(|pid| pid.split_once(']')) {
+            record.process_id = pid.to_string();
+            after_tag = after;
+        }
+        rest = after_tag.strip_prefix(':').unwrap_or(after_tag);
+    }
+    record.message = rest.trim_start_matches(' ').to_string();
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// RFC 3164 timestamp `Mmm dd hh:mm:ss`, in the current year as it has none
+///
+/// A date more than a day ahead was sent last year (December message received in January).
+fn parse_bsd_timestamp(timestamp: &str) -> Option<String> {
+    let now = Utc::now().naive_utc();
+    let parse = |year: i32| NaiveDateTime::parse_from_str(&format!("{} {}", year, timestamp), "%Y %b %e %H:%M:%S").ok();
+    let mut datetime = parse(now.year())?;
+    if datetime > now + Duration::days(1) {
+        datetime = parse(now.year() - 1)?;
+    }
+    Some(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Host part of a `host:port` source address
+pub(crate) fn source_host(source: &str) -> &str {
+    source
+        .rsplit_once(':')
+        .map(|(host, _)| host.trim_start_matches('[').trim_end_matches(']'))
+        .unwrap_or(source)
+}
@@ -1,28 +1,53 @@
 //! File writer with ordering and rotation
 //!
-//! Handles ordered writing of log messages to files with rotation.
+//! Handles ordered writing of log records to files with rotation.
 
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::{
-    fs::{self, File},
+    fs::{File, OpenOptions},
     io::AsyncWriteExt,
-    sync::mpsc,
-    time::{sleep, Duration},
+    sync::{mpsc, oneshot},
+    time::{sleep, sleep_until, Duration, Instant},
 };
+use chrono::{DateTime, Utc};
 
+use crate::core::compression::Compression;
+use crate::core::formatters::{format_gap, format_record, OutputFormat, TextTemplate};
+use crate::core::records::{LogField, LogRecord};
+use crate::core::rotation::{list_rotated_files, RotationInterval, Rotator};
+use crate::utils::helpers::{parse_sequence_number, read_tail_lines};
 
 
 
+
+/// Bytes read back from the end of a log to recover its highest sequence number
+const RECOVERY_TAIL_BYTES: u64 = 256 * 1024;
+
+//-----------------------------------------------------------------------------------------------
+
 /// Log writer configuration
 #[derive(Clone)]
 pub struct WriterConfig {
+    pub log_path: PathBuf,
     pub initial_batch_size: usize,
     pub buffer_size: usize,
     pub max_retries: usize,
     pub retry_delay_ms: u64,
     pub max_file_bytes: u64,
     pub backup_count: usize,
+    pub rotation_interval: Option<RotationInterval>,
+    pub compression: Option<Compression>,
+    pub retention_max_age: Option<Duration>,
+    pub retention_max_bytes: Option<u64>,
+    pub output_format: OutputFormat,
+    pub text_template: TextTemplate,
+    pub text_fields: Vec<LogField>,
+    pub resume_sequence: bool,
+    pub durability: DurabilityMode,
+    pub gap_timeout: Option<Duration>,
 }
 
 //-----------------------------------------------------------------------------------------------
@@ -30,13 +55,283 @@ pub struct WriterConfig {
 impl Default for WriterConfig {
     fn default() -> Self {
         Self {
+            log_path: crate::utils::helpers::get_exec_parent_dir().join("logs").join("_main.log"),
             initial_batch_size: 100,
             buffer_size: 1024,
             max_retries: 3,
             retry_delay_ms: 100,
-            max_file_bytes: 1 * 1024 * 1024, // 1 MB
+            max_file_bytes: 1024 * 1024, // 1 MB
             backup_count: 10,
+            rotation_interval: None,
+            compression: None,
+            retention_max_age: None,
+            retention_max_bytes: None,
+            output_format: OutputFormat::Text,
+            text_template: TextTemplate::default(),
+            text_fields: Vec::new(),
+            resume_sequence: false,
+            durability: DurabilityMode::NoSync,
+            gap_timeout: Some(Duration::from_millis(1000)),
+        }
+    }
+}
+
+/// When written data is forced to disk (fsync)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DurabilityMode {
+    /// Never fsync, rely on the OS write-back
+    NoSync,
+    /// fsync after every written batch
+    Batch,
+    /// fsync at most once per interval while there is unsynced data
+    Interval(Duration),
+    /// fsync before acknowledging : acknowledged records are durable
+    Ack,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl DurabilityMode {
+    /// Parse `none`, `batch`, `ack` or `<N>ms` (e.g. `200ms`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(DurabilityMode::NoSync),
+            "batch" => Some(DurabilityMode::Batch),
+            "ack" => Some(DurabilityMode::Ack),
+            _ => {
+                let millis = name.strip_suffix("ms")?.parse::<u64>().ok()?;
+                (millis > 0).then_some(DurabilityMode::Interval(Duration::from_millis(millis)))
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Pipeline stage a record must reach before its sender is acknowledged
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AckStage {
+    /// Accepted by the writer queue
+    Queued,
+    /// Written to the log file (OS page cache)
+    Written,
+    /// Written and fsynced
+    Durable,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Record queued for the writer task, with its optional acknowledgement
+struct QueuedRecord {
+    record: LogRecord,
+    ack: Option<(AckStage, oneshot::Sender<()>)>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Writer counters, shared with the writer handle
+#[derive(Default)]
+pub struct WriterStats {
+    /// Sequence numbers skipped by the gap timeout
+    pub skipped_sequences: AtomicU64,
+    /// Records arriving after their gap was skipped, written out of order
+    pub late_records: AtomicU64,
+    /// fsyncs of the log file, as the durability mode, rotation and shutdown require
+    pub syncs: AtomicU64,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Shared entry point of the writer pipeline : one sequencer feeding one writer task
+#[derive(Clone)]
+pub struct WriterHandle {
+    writer_tx: mpsc::Sender<QueuedRecord>,
+    sequence_counter: Arc<AtomicU64>,
+    ack_stage: AckStage,
+    stats: Arc<WriterStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl WriterHandle {
+    /// Assign the next sequence number and queue the record for writing
+    pub async fn send(&self, record: LogRecord) -> Result<u64, String> {
+        self.send_and_wait(record, AckStage::Queued).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue the record and wait for the stage required by the durability mode before returning
+    pub async fn send_acknowledged(&self, record: LogRecord) -> Result<u64, String> {
+        self.send_and_wait(record, self.ack_stage).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue the record, the returned acknowledgement resolves at the stage required by the durability mode
+    pub async fn queue_acknowledged(&self, record: LogRecord) -> Result<PendingAck, String> {
+        self.queue(record, self.ack_stage).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue the record and wait until it reached `stage`
+    pub async fn send_and_wait(&self, record: LogRecord, stage: AckStage) -> Result<u64, String> {
+        self.queue(record, stage).await?.wait().await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue the record, the returned acknowledgement resolves once it reached `stage`
+    ///
+    /// Lets a connection keep reading while earlier records are still on their way to the file.
+    /// As in `try_send`, the queue slot is reserved before the sequence number is assigned : a
+    /// send cancelled or waiting behind a full queue leaves no gap in the sequence.
+    pub async fn queue(&self, record: LogRecord, stage: AckStage) -> Result<PendingAck, String> {
+        let permit = self
+            .writer_tx
+            .reserve()
+            .await
+            .map_err(|e| format!("failed to queue message: {}", e))?;
+        let sequence = self.sequence_counter.fetch_add(1, Ordering::SeqCst);
+        Ok(Self::send_permit(permit, record, sequence, stage))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // Send `record` numbered `sequence` through a reserved queue slot
+    fn send_permit(permit: mpsc::Permit<'_, QueuedRecord>, mut record: LogRecord, sequence: u64, stage: AckStage) -> PendingAck {
+        record.sequence = sequence;
+
+        let (ack, ack_rx) = match stage {
+            AckStage::Queued => (None, None),
+            _ => {
+                let (ack_tx, ack_rx) = oneshot::channel();
+                (Some((stage, ack_tx)), Some(ack_rx))
+            }
+        };
+
+        permit.send(QueuedRecord { record, ack });
+        PendingAck { sequence, ack_rx }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue the record without waiting, fails when the writer queue is full
+    ///
+    /// The queue slot is reserved before the sequence number is assigned, so a rejected record
+    /// leaves no gap in the sequence.
+    pub fn try_send(&self, mut record: LogRecord) -> Result<u64, String> {
+        let permit = self.writer_tx.try_reserve().map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => "writer queue full".to_string(),
+            mpsc::error::TrySendError::Closed(_) => "writer closed".to_string(),
+        })?;
+        let sequence = self.sequence_counter.fetch_add(1, Ordering::SeqCst);
+        record.sequence = sequence;
+        permit.send(QueuedRecord { record, ack: None });
+        Ok(sequence)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Writer counters
+    pub fn stats(&self) -> &WriterStats {
+        &self.stats
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Take the next sequence number without queuing a record, as a record lost on its way to the
+    /// writer would
+    #[cfg(feature = "test-util")]
+    pub fn skip_sequence(&self) -> u64 {
+        self.sequence_counter.fetch_add(1, Ordering::SeqCst)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Queue a record under an already taken `sequence`, e.g. one skipped to simulate a late record
+    #[cfg(feature = "test-util")]
+    pub async fn queue_with_sequence(&self, record: LogRecord, sequence: u64, stage: AckStage) -> Result<PendingAck, String> {
+        let permit = self
+            .writer_tx
+            .reserve()
+            .await
+            .map_err(|e| format!("failed to queue message: {}", e))?;
+        Ok(Self::send_permit(permit, record, sequence, stage))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Handle without writer task : records go to a queue of `capacity` read back with the returned
+    /// `QueuedRecords`, e.g. to check what a server queues
+    #[cfg(feature = "test-util")]
+    pub fn detached(capacity: usize) -> (Self, QueuedRecords) {
+        let (writer_tx, writer_rx) = mpsc::channel::<QueuedRecord>(capacity);
+        let handle = Self {
+            writer_tx,
+            sequence_counter: Arc::new(AtomicU64::new(0)),
+            ack_stage: AckStage::Queued,
+            stats: Arc::new(WriterStats::default()),
+        };
+        (handle, QueuedRecords { rx: writer_rx })
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Records queued by a detached writer handle, acknowledged as they are taken
+#[cfg(feature = "test-util")]
+pub struct QueuedRecords {
+    rx: mpsc::Receiver<QueuedRecord>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[cfg(feature = "test-util")]
+impl QueuedRecords {
+    /// Next queued record, waiting for it, None once every handle is dropped
+    pub async fn recv(&mut self) -> Option<LogRecord> {
+        self.rx.recv().await.map(Self::acknowledge)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Next queued record if there is one
+    pub fn try_recv(&mut self) -> Option<LogRecord> {
+        self.rx.try_recv().ok().map(Self::acknowledge)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    // Resolve the acknowledgement of a taken record, whatever its stage
+    fn acknowledge(queued: QueuedRecord) -> LogRecord {
+        if let Some((_, ack_tx)) = queued.ack {
+            let _ = ack_tx.send(());
+        }
+        queued.record
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Acknowledgement of a queued record, resolves once the record reached the requested stage
+pub struct PendingAck {
+    sequence: u64,
+    ack_rx: Option<oneshot::Receiver<()>>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl PendingAck {
+    /// Wait for the requested stage, returns the record sequence number
+    pub async fn wait(self) -> Result<u64, String> {
+        if let Some(ack_rx) = self.ack_rx {
+            ack_rx
+                .await
+                .map_err(|_| format!("record {} not acknowledged, writer stopped", self.sequence))?;
         }
+        Ok(self.sequence)
     }
 }
 
@@ -46,64 +341,140 @@ impl Default for WriterConfig {
 pub struct LogWriter {
     config: WriterConfig,
     base_file_path: PathBuf,
+    first_sequence: u64,
 }
 
 //-----------------------------------------------------------------------------------------------
 
 impl LogWriter {
     /// Create new log writer
-    pub async fn new() -> Result<Self, std::io::Error> {
-        let base_file_path = crate::utils::helpers::get_exec_parent_dir()
-            .join("logs")
-            .join("_main.log");
+    pub async fn new(config: WriterConfig) -> Result<Self, std::io::Error> {
+        let base_file_path = config.log_path.clone();
+        if let Some(dir) = base_file_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        // Continue numbering after the highest sequence written by a previous run
+        if config.resume_sequence && config.output_format == OutputFormat::Text && !config.text_template.starts_with_sequence() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--resume_sequence needs a text template starting with {seq}",
+            ));
+        }
+        let first_sequence = if config.resume_sequence {
+            Self::recover_next_sequence(&base_file_path).await?.unwrap_or(0)
+        } else {
+            0
+        };
+        if first_sequence > 0 {
+            println!("Log writer : resuming sequence at {}", first_sequence);
+        }
             
         Ok(Self {
-            config: WriterConfig::default(),
+            config,
             base_file_path,
+            first_sequence,
         })
     }
     
     //-----------------------------------------------------------------------------------------------
     
-    /// Start the writer task
-    pub fn start_writer_task(&self) -> mpsc::Sender<String> {
-        let (writer_tx, writer_rx) = mpsc::channel::<String>(self.config.buffer_size);
+    /// Start the writer task, must be called once : every protocol shares the returned handle
+    pub fn start_writer_task(&self) -> WriterHandle {
+        let (writer_tx, writer_rx) = mpsc::channel::<QueuedRecord>(self.config.buffer_size);
         let base_path = self.base_file_path.clone();
         let config = self.config.clone();
+        let first_sequence = self.first_sequence;
+        let ack_stage = match config.durability {
+            DurabilityMode::Ack => AckStage::Durable,
+            _ => AckStage::Queued,
+        };
+        let stats = Arc::new(WriterStats::default());
+        let task_stats = stats.clone();
         
         tokio::spawn(async move {
-            if let Err(e) = Self::writer_task(writer_rx, base_path, config).await {
+            if let Err(e) = Self::writer_task(writer_rx, base_path, config, first_sequence, task_stats).await {
                 eprintln!("Writer task failed: {}", e);
             }
         });
         
-        writer_tx
+        WriterHandle {
+            writer_tx,
+            sequence_counter: Arc::new(AtomicU64::new(first_sequence)),
+            ack_stage,
+            stats,
+        }
     }
     
     //-----------------------------------------------------------------------------------------------
     
     /// Main writer task implementation
     async fn writer_task(
-        mut rx: mpsc::Receiver<String>,
+        mut rx: mpsc::Receiver<QueuedRecord>,
         base_file_path: PathBuf,
         config: WriterConfig,
+        first_sequence: u64,
+        stats: Arc<WriterStats>,
     ) -> tokio::io::Result<()> {
-        let mut file = File::create(&base_file_path).await?;
-        let mut file_size = 0u64;
-        let mut buffer: BTreeMap<u64, String> = BTreeMap::new();
-        let mut current_sequence: u64 = 0;
+        let mut log_file = ActiveFile::open(&base_file_path, &config, stats.clone()).await?;
+        let mut buffer: BTreeMap<u64, QueuedRecord> = BTreeMap::new();
+        let mut current_sequence: u64 = first_sequence;
         let mut batch_size = config.initial_batch_size;
+        let mut gap_deadline: Option<Instant> = None;
+
+        loop {
+            let queued = tokio::select! {
+                queued = rx.recv() => match queued {
+                    Some(queued) => Some(queued),
+                    None => break,
+                },
+                _ = wait_until(log_file.rotator.next_boundary()) => {
+                    log_file.rotate().await?;
+                    continue;
+                }
+                _ = wait_until_instant(log_file.sync_deadline) => {
+                    log_file.sync().await?;
+                    continue;
+                }
+                _ = wait_until_instant(gap_deadline) => {
+                    // A slow rotation or fsync may have kept the missing sequence queued past the
+                    // deadline : take what is already queued before declaring the gap
+                    for _ in 0..rx.len() {
+                        let Ok(queued) = rx.try_recv() else {
+                            break;
+                        };
+                        Self::buffer_record(queued, current_sequence, &mut buffer, &mut log_file, &config, &stats).await?;
+                    }
 
-        while let Some(message) = rx.recv().await {
-            // Parse sequence number and message
-            if let Some((seq_str, log_data)) = message.split_once(' ') {
-                if let Ok(sequence) = seq_str.parse::<u64>() {
-                    buffer.insert(sequence, log_data.to_string());
+                    // The next sequence never arrived : skip to the first buffered record
+                    let next_sequence = buffer.keys().next().copied().filter(|next| *next != current_sequence);
+                    if let Some(next_sequence) = next_sequence {
+                        let skipped = next_sequence - current_sequence;
+                        log_file.write_gap(current_sequence, next_sequence - 1, &config).await?;
+                        stats.skipped_sequences.fetch_add(skipped, Ordering::Relaxed);
+                        eprintln!(
+                            "Log writer : gap timeout, skipped sequences {}..={} ({} skipped in total)",
+                            current_sequence,
+                            next_sequence - 1,
+                            stats.skipped_sequences.load(Ordering::Relaxed)
+                        );
+                        current_sequence = next_sequence;
+                    }
+                    None
                 }
+            };
+
+            let mut next = queued;
+            while let Some(queued) = next {
+                Self::buffer_record(queued, current_sequence, &mut buffer, &mut log_file, &config, &stats).await?;
+
+                // Take whatever else is already queued, so one batch (and one fsync) covers it
+                next = if buffer.len() < batch_size { rx.try_recv().ok() } else { None };
             }
 
             // Process batch if ready
-            while buffer.len() >= batch_size || buffer.contains_key(&current_sequence) {
+            let sequence_before = current_sequence;
+            while buffer.contains_key(&current_sequence) {
                 let mut batch = Vec::new();
 
                 for _ in 0..batch_size {
@@ -116,20 +487,29 @@ impl LogWriter {
                 }
 
                 if !batch.is_empty() {
-                    Self::write_batch(&mut file, &mut file_size, &batch, &config).await?;
+                    // A period boundary passed without wake-up (e.g. clock jump) : rotate before writing
+                    if log_file.rotator.time_due(Utc::now()) {
+                        log_file.rotate().await?;
+                    }
+
+                    log_file.write_batch(batch, &config).await?;
 
                     // Rotate file if size exceeds limit
-                    if file_size >= config.max_file_bytes {
-                        file.flush().await?;
-                        Self::rotate_files(&base_file_path, config.backup_count).await?;
-                        file = File::create(&base_file_path).await?;
-                        file_size = 0;
+                    if log_file.rotator.size_due(log_file.size) {
+                        log_file.rotate().await?;
                     }
-
-                    file.flush().await?;
                 }
             }
 
+            // Records buffered behind a missing sequence : (re)arm the gap timeout on progress
+            gap_deadline = match config.gap_timeout {
+                Some(_) if buffer.is_empty() => None,
+                Some(timeout) if gap_deadline.is_none() || current_sequence != sequence_before => {
+                    Some(Instant::now() + timeout)
+                }
+                _ => gap_deadline,
+            };
+
             // Adjust batch size dynamically
             if buffer.len() > batch_size {
                 batch_size = (batch_size * 2).min(1000);
@@ -139,63 +519,244 @@ impl LogWriter {
         }
 
         // Flush remaining messages
-        for (_, data) in buffer {
-            let log_entry = format!("{}\n", data);
-            file.write_all(log_entry.as_bytes()).await?;
+        let remaining: Vec<QueuedRecord> = buffer.into_values().collect();
+        if !remaining.is_empty() {
+            log_file.write_batch(remaining, &config).await?;
         }
-
-        file.flush().await?;
-        Ok(())
+        log_file.close().await
     }
     
     //-----------------------------------------------------------------------------------------------
-    
-    /// Write batch with retry logic
-    async fn write_batch(
-        file: &mut File,
-        file_size: &mut u64,
-        batch: &[String],
+
+    /// Buffer a received record until its turn, or write it now if it arrived after its gap was skipped
+    async fn buffer_record(
+        queued: QueuedRecord,
+        current_sequence: u64,
+        buffer: &mut BTreeMap<u64, QueuedRecord>,
+        log_file: &mut ActiveFile,
         config: &WriterConfig,
+        stats: &WriterStats,
     ) -> tokio::io::Result<()> {
-        for attempt in 0..=config.max_retries {
-            let mut success = true;
-            for data in batch {
-                let log_entry = format!("{}\n", data);
-                if file.write_all(log_entry.as_bytes()).await.is_err() {
-                    success = false;
-                    break;
+        if queued.record.sequence < current_sequence {
+            // Written late rather than lost
+            eprintln!("Log writer : late record {} written out of order", queued.record.sequence);
+            stats.late_records.fetch_add(1, Ordering::Relaxed);
+            log_file.write_batch(vec![queued], config).await?;
+        } else {
+            buffer.insert(queued.record.sequence, queued);
+        }
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Sequence following the highest one at the end of the current log, or of the latest backup
+    /// if it is empty, None when there is no log yet
+    ///
+    /// The highest rather than the last : late records are written out of order, and a message
+    /// may span several lines. A log without any readable sequence fails rather than restart at 0.
+    async fn recover_next_sequence(base_path: &Path) -> tokio::io::Result<Option<u64>> {
+        let latest_backup = list_rotated_files(base_path).await?.into_iter().next().map(|file| file.path);
+        for path in std::iter::once(base_path.to_path_buf()).chain(latest_backup) {
+            let lines = match Compression::from_path(&path) {
+                Some(compression) => {
+                    let compressed_path = path.clone();
+                    tokio::task::spawn_blocking(move || compression.read_tail_lines(&compressed_path, RECOVERY_TAIL_BYTES))
+                        .await
+                        .map_err(tokio::io::Error::other)??
                 }
-                *file_size += data.len() as u64;
+                None => read_tail_lines(&path, RECOVERY_TAIL_BYTES).await?,
+            };
+            if lines.is_empty() {
+                continue;
+            }
+            let sequence = lines.iter().filter_map(|line| Self::parse_line_sequence(line)).max();
+            return match sequence {
+                Some(sequence) => Ok(Some(sequence + 1)),
+                None => Err(tokio::io::Error::new(
+                    tokio::io::ErrorKind::InvalidData,
+                    format!("no sequence number found at the end of {}, cannot resume the sequence", path.display()),
+                )),
+            };
+        }
+        Ok(None)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Sequence number of a written line, text (leading number) or JSON (`seq` key)
+    fn parse_line_sequence(line: &str) -> Option<u64> {
+        if line.starts_with('{') {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            return value.get("seq")?.as_u64();
+        }
+        parse_sequence_number(line.trim_start()).map(|(sequence, _)| sequence)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Current log file with its rotation and fsync state
+struct ActiveFile {
+    file: File,
+    path: PathBuf,
+    size: u64,
+    rotator: Rotator,
+    durability: DurabilityMode,
+    sync_deadline: Option<Instant>,
+    stats: Arc<WriterStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl ActiveFile {
+    /// Open the current log file, appending to the log left by a previous run
+    async fn open(path: &Path, config: &WriterConfig, stats: Arc<WriterStats>) -> tokio::io::Result<Self> {
+        let file = Self::open_log_file(path).await?;
+        let metadata = file.metadata().await?;
+        let size = metadata.len();
+
+        // Data left by a previous run belongs to the period it was written in
+        let file_modified = (size > 0)
+            .then(|| metadata.modified().ok().map(DateTime::<Utc>::from))
+            .flatten();
+        let rotator = Rotator::new(path, config, file_modified);
+        rotator.enforce_retention().await?;
+
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            size,
+            rotator,
+            durability: config.durability,
+            sync_deadline: None,
+            stats,
+        })
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Write batch with retry logic, then fsync and acknowledge as the durability mode requires
+    async fn write_batch(&mut self, batch: Vec<QueuedRecord>, config: &WriterConfig) -> tokio::io::Result<()> {
+        // Formatting happens only here, at the sink
+        let mut lines = String::new();
+        let mut acks = Vec::new();
+        for queued in batch {
+            lines.push_str(&format_record(&queued.record, config.output_format, &config.text_template, &config.text_fields));
+            lines.push('\n');
+            acks.extend(queued.ack);
+        }
+
+        self.write_lines(&lines, config).await?;
+
+        let wants_durable = acks.iter().any(|(stage, _)| *stage == AckStage::Durable);
+        match self.durability {
+            DurabilityMode::Batch | DurabilityMode::Ack => self.sync().await?,
+            _ if wants_durable => self.sync().await?,
+            DurabilityMode::Interval(interval) if self.sync_deadline.is_none() => {
+                self.sync_deadline = Some(Instant::now() + interval);
             }
+            _ => {}
+        }
+
+        for (_, ack_tx) in acks {
+            let _ = ack_tx.send(());
+        }
+        Ok(())
+    }
 
-            if success {
+    //-----------------------------------------------------------------------------------------------
+
+    /// Write a marker line for sequences `from..=to` skipped by the gap timeout
+    async fn write_gap(&mut self, from: u64, to: u64, config: &WriterConfig) -> tokio::io::Result<()> {
+        let mut line = format_gap(from, to, config.output_format);
+        line.push('\n');
+        self.write_lines(&line, config).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Write formatted lines with retry logic
+    async fn write_lines(&mut self, lines: &str, config: &WriterConfig) -> tokio::io::Result<()> {
+        for attempt in 0..=config.max_retries {
+            if self.file.write_all(lines.as_bytes()).await.is_ok() {
+                self.size += lines.len() as u64;
                 break;
             } else if attempt < config.max_retries {
                 sleep(Duration::from_millis(config.retry_delay_ms)).await;
             } else {
-                return Err(tokio::io::Error::new(
-                    tokio::io::ErrorKind::Other,
-                    "Write failed after maximum retries",
-                ));
+                return Err(tokio::io::Error::other("Write failed after maximum retries"));
             }
         }
+        self.file.flush().await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Force written data to disk
+    async fn sync(&mut self) -> tokio::io::Result<()> {
+        self.file.sync_data().await?;
+        self.sync_deadline = None;
+        self.stats.syncs.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
-    
+
     //-----------------------------------------------------------------------------------------------
-    
-    /// Rotate log files
-    async fn rotate_files(base_path: &PathBuf, backup_count: usize) -> tokio::io::Result<()> {
-        for i in (1..=backup_count).rev() {
-            let old_path = base_path.with_extension(format!("log.{}", i - 1));
-            let new_path = base_path.with_extension(format!("log.{}", i));
-
-            if fs::metadata(&old_path).await.is_ok() {
-                fs::rename(&old_path, &new_path).await?;
-            }
+
+    /// Rotate the current file if it holds data and reopen a fresh one
+    async fn rotate(&mut self) -> tokio::io::Result<()> {
+        let now = Utc::now();
+        if self.size == 0 {
+            self.rotator.start_period(now);
+            return Ok(());
         }
 
-        fs::rename(&base_path, &base_path.with_extension("log.0")).await?;
+        self.file.flush().await?;
+        if self.durability != DurabilityMode::NoSync {
+            self.sync().await?;
+        }
+        self.rotator.rotate(now).await?;
+        self.file = Self::open_log_file(&self.path).await?;
+        self.size = 0;
         Ok(())
     }
-}
\ No newline at end of file
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Flush and sync on shutdown, wait for background compression / retention
+    async fn close(mut self) -> tokio::io::Result<()> {
+        self.file.flush().await?;
+        if self.durability != DurabilityMode::NoSync {
+            self.sync().await?;
+        }
+        self.rotator.wait_background().await;
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Open the current log file in append mode, creating it if missing
+    async fn open_log_file(path: &Path) -> tokio::io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path).await
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Sleep until `deadline`, forever if there is none
+async fn wait_until(deadline: Option<DateTime<Utc>>) {
+    match deadline {
+        Some(deadline) => sleep((deadline - Utc::now()).to_std().unwrap_or_default()).await,
+        None => std::future::pending().await,
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Sleep until `deadline` on the monotonic clock, forever if there is none
+async fn wait_until_instant(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
@@ -12,3 +12,4 @@ pub mod utils;
 // Re-export main components
 pub use core::servers::LogServer;
 pub use common::config::ServerConfig;
+pub use core::writers::WriterConfig;
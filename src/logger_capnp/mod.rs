@@ -1,3 +1,4 @@
 //! Capnp message struct
 
+#[allow(clippy::module_inception)]
 pub mod logger_msg;
\ No newline at end of file
@@ -3,8 +3,21 @@
 //! Centralized logging server that handles both TCP socket (Cap'n Proto)
 //! and gRPC log messages with ordered file writing and rotation.
 
-use clap::{Arg, Command};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use clap::{Arg, ArgMatches, Command};
+use log_server::common::auth::TokenAuth;
+use log_server::common::config::DEFAULT_MAX_DATAGRAM_BYTES;
+use log_server::common::tls::TlsConfig;
+use log_server::core::compression::Compression;
+use log_server::core::formatters::{OutputFormat, TextTemplate, DEFAULT_TEXT_TEMPLATE};
+use log_server::core::records::LogField;
+use log_server::core::rotation::RotationInterval;
 use log_server::core::servers::LogServer;
+use log_server::utils::parse_byte_size;
+use log_server::core::writers::DurabilityMode;
+use log_server::{ServerConfig, WriterConfig};
 
 
 
@@ -29,13 +42,273 @@ fn main() {
         .arg(Arg::new("tcp_only")
             .long("tcp_only")
             .action(clap::ArgAction::SetTrue))  // Add this flag
+        .arg(Arg::new("udp_port")
+            .long("udp_port")
+            .help("also accept one packed Cap'n Proto message per UDP datagram on this port (best effort)"))
+        .arg(Arg::new("max_datagram_bytes")
+            .long("max_datagram_bytes")
+            .help("drop UDP datagrams larger than this")
+            .default_value("65507"))
+        .arg(Arg::new("http_port")
+            .long("http_port")
+            .help("also accept JSON log messages on POST /v1/logs on this port"))
+        .arg(Arg::new("syslog_port")
+            .long("syslog_port")
+            .help("also accept RFC 5424 / RFC 3164 syslog messages on this port, UDP and TCP"))
+        .arg(Arg::new("forward_port")
+            .long("forward_port")
+            .help("also accept Fluent Forward protocol messages (Fluent Bit, Fluentd) on this port"))
+        .arg(Arg::new("gelf_port")
+            .long("gelf_port")
+            .help("also accept GELF messages on this port, UDP (chunked, compressed) and TCP"))
+        .arg(Arg::new("unix_socket")
+            .long("unix_socket")
+            .help("also listen on this Unix domain socket path (same framing as the TCP socket)"))
+        .arg(Arg::new("unix_socket_mode")
+            .long("unix_socket_mode")
+            .help("octal file permissions of the Unix socket")
+            .default_value("660"))
+        .arg(Arg::new("max_frame_bytes")
+            .long("max_frame_bytes")
+            .help("reject TCP frames larger than this (e.g. 16M), the client is disconnected")
+            .default_value("16M"))
+        .arg(Arg::new("tls_cert")
+            .long("tls_cert")
+            .help("PEM certificate chain, enables TLS on the TCP and gRPC listeners (requires --tls_key)"))
+        .arg(Arg::new("tls_key")
+            .long("tls_key")
+            .help("PEM private key of --tls_cert"))
+        .arg(Arg::new("tls_client_ca")
+            .long("tls_client_ca")
+            .help("PEM CA certificates verifying client certificates (mutual TLS)"))
+        .arg(Arg::new("tls_client_auth_optional")
+            .long("tls_client_auth_optional")
+            .help("with --tls_client_ca, also accept clients without a certificate")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("auth_tokens")
+            .long("auth_tokens")
+            .help("file of '<identity> <token>' lines, clients must authenticate with one of the tokens"))
+        .arg(Arg::new("allow_unauthenticated")
+            .long("allow_unauthenticated")
+            .help("with --auth_tokens, still run the UDP, syslog and GELF listeners, which cannot authenticate clients")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("max_file_bytes")
+            .long("max_file_bytes")
+            .help("rotate the log file at this size (e.g. 1048576, 100M), 0 disables size rotation")
+            .default_value("1048576"))
+        .arg(Arg::new("backup_count")
+            .long("backup_count")
+            .help("number of rotated log files kept, 0 discards the rotated file")
+            .default_value("10"))
+        .arg(Arg::new("rotate_every")
+            .long("rotate_every")
+            .help("time based rotation (UTC) : daily, hourly or <N>m, e.g. 15m"))
+        .arg(Arg::new("compression")
+            .long("compression")
+            .help("compress rotated log files : gzip or zstd"))
+        .arg(Arg::new("retention_days")
+            .long("retention_days")
+            .help("delete rotated log files older than this many days"))
+        .arg(Arg::new("retention_max_bytes")
+            .long("retention_max_bytes")
+            .help("keep the log files under this total size (e.g. 10G), oldest rotated files deleted first"))
+        .arg(Arg::new("durability")
+            .long("durability")
+            .help("fsync policy : none, batch, <N>ms or ack (gRPC success returned once durable)")
+            .default_value("none"))
+        .arg(Arg::new("resume_sequence")
+            .long("resume_sequence")
+            .help("continue sequence numbering after the highest sequence at the end of the existing log")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("gap_timeout_ms")
+            .long("gap_timeout_ms")
+            .help("skip a missing sequence number after waiting this long for it, 0 waits forever")
+            .default_value("1000"))
+        .arg(Arg::new("output_format")
+            .long("output_format")
+            .help("log file format : text or json (JSON Lines)")
+            .default_value("text"))
+        .arg(Arg::new("text_template")
+            .long("text_template")
+            .help("text line template, e.g. '{seq} {timestamp} [{level}] {logger_name}: {message}'")
+            .default_value(DEFAULT_TEXT_TEMPLATE))
+        .arg(Arg::new("text_fields")
+            .long("text_fields")
+            .help("comma separated extra fields rendered by {extras} in text lines, e.g. process_id,stack_trace")
+            .default_value(""))
         .get_matches();
     
     let name = matches.get_one::<String>("name").unwrap();
     let host = matches.get_one::<String>("host").unwrap();
-    let port = matches.get_one::<String>("port").unwrap().parse::<u16>().unwrap();
-    let grpc_port = matches.get_one::<String>("grpc_port").unwrap().parse::<u16>().unwrap();
+    let port = parse_port(&matches, "port").unwrap();
+    let grpc_port = parse_port(&matches, "grpc_port").unwrap();
     let tcp_only = matches.get_flag("tcp_only");  // Get the flag value
+    let max_file_bytes = parse_byte_size(matches.get_one::<String>("max_file_bytes").unwrap()).unwrap_or_else(|| {
+        eprintln!("{} : invalid --max_file_bytes", name);
+        std::process::exit(1);
+    });
+    let max_frame_bytes = parse_byte_size(matches.get_one::<String>("max_frame_bytes").unwrap())
+        .and_then(|size| usize::try_from(size).ok())
+        .filter(|size| *size <= u32::MAX as usize)
+        .unwrap_or_else(|| {
+            eprintln!("{} : invalid --max_frame_bytes, at most 4G", name);
+            std::process::exit(1);
+        });
+    let udp_port = parse_port(&matches, "udp_port");
+    let max_datagram_bytes = parse_byte_size(matches.get_one::<String>("max_datagram_bytes").unwrap())
+        .and_then(|size| usize::try_from(size).ok())
+        .filter(|size| *size > 0 && *size <= DEFAULT_MAX_DATAGRAM_BYTES)
+        .unwrap_or_else(|| {
+            eprintln!("{} : invalid --max_datagram_bytes, at most {}", name, DEFAULT_MAX_DATAGRAM_BYTES);
+            std::process::exit(1);
+        });
+    let http_port = parse_port(&matches, "http_port");
+    let syslog_port = parse_port(&matches, "syslog_port");
+    let forward_port = parse_port(&matches, "forward_port");
+    let gelf_port = parse_port(&matches, "gelf_port");
+    let unix_socket = matches.get_one::<String>("unix_socket").map(PathBuf::from);
+    let unix_socket_mode = u32::from_str_radix(matches.get_one::<String>("unix_socket_mode").unwrap(), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .unwrap_or_else(|| {
+            eprintln!("{} : invalid --unix_socket_mode, expected octal permissions e.g. 660", name);
+            std::process::exit(1);
+        });
+    let tls = match (matches.get_one::<String>("tls_cert"), matches.get_one::<String>("tls_key")) {
+        (Some(cert), Some(key)) => {
+            let mut tls = TlsConfig::new(Path::new(cert), Path::new(key));
+            tls.client_ca_path = matches.get_one::<String>("tls_client_ca").map(PathBuf::from);
+            tls.client_auth_optional = matches.get_flag("tls_client_auth_optional");
+            // Fail at startup rather than in each listener
+            if let Err(e) = tls.rustls_server_config() {
+                eprintln!("{} : invalid TLS configuration - {}", name, e);
+                std::process::exit(1);
+            }
+            Some(tls)
+        }
+        (None, None) if matches.get_one::<String>("tls_client_ca").is_none() => None,
+        _ => {
+            eprintln!("{} : --tls_cert and --tls_key are required together to enable TLS", name);
+            std::process::exit(1);
+        }
+    };
+    let auth = matches.get_one::<String>("auth_tokens").map(|path| {
+        let auth = TokenAuth::load(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("{} : invalid --auth_tokens - {}", name, e);
+            std::process::exit(1);
+        });
+        println!("{} : token authentication enabled, {} client token(s)", name, auth.len());
+        Arc::new(auth)
+    });
+
+    // UDP, syslog and GELF clients cannot authenticate : with tokens, they only run on explicit request
+    let unauthenticated: Vec<&str> = [(udp_port, "--udp_port"), (syslog_port, "--syslog_port"), (gelf_port, "--gelf_port")]
+        .into_iter()
+        .filter_map(|(port, option)| port.map(|_| option))
+        .collect();
+    if auth.is_some() && !unauthenticated.is_empty() {
+        let listeners = unauthenticated.join(", ");
+        if !matches.get_flag("allow_unauthenticated") {
+            eprintln!(
+                "{} : {} cannot authenticate clients, add --allow_unauthenticated to run them with --auth_tokens",
+                name, listeners
+            );
+            std::process::exit(1);
+        }
+        println!("{} : warning, messages on {} are not authenticated despite --auth_tokens", name, listeners);
+    }
+    let backup_count = matches.get_one::<String>("backup_count").unwrap().parse::<usize>().unwrap_or_else(|_| {
+        eprintln!("{} : invalid --backup_count", name);
+        std::process::exit(1);
+    });
+    let resume_sequence = matches.get_flag("resume_sequence");
+
+    let rotation_interval = matches.get_one::<String>("rotate_every").map(|every| {
+        RotationInterval::from_name(every).unwrap_or_else(|| {
+            eprintln!("{} : invalid --rotate_every, expected daily, hourly or <N>m", name);
+            std::process::exit(1);
+        })
+    });
+
+    let output_format = match OutputFormat::from_name(matches.get_one::<String>("output_format").unwrap()) {
+        Some(format) => format,
+        None => {
+            eprintln!("{} : invalid --output_format, expected text or json", name);
+            std::process::exit(1);
+        }
+    };
+
+    let compression = matches.get_one::<String>("compression").map(|codec| {
+        Compression::from_name(codec).unwrap_or_else(|| {
+            eprintln!("{} : invalid --compression, expected gzip or zstd", name);
+            std::process::exit(1);
+        })
+    });
+
+    let retention_max_age = matches.get_one::<String>("retention_days").map(|days| {
+        let days = days.parse::<u64>().unwrap_or_else(|_| {
+            eprintln!("{} : invalid --retention_days", name);
+            std::process::exit(1);
+        });
+        let seconds = days.checked_mul(24 * 3600).unwrap_or_else(|| {
+            eprintln!("{} : --retention_days too large", name);
+            std::process::exit(1);
+        });
+        std::time::Duration::from_secs(seconds)
+    });
+
+    let retention_max_bytes = matches.get_one::<String>("retention_max_bytes").map(|size| {
+        parse_byte_size(size).unwrap_or_else(|| {
+            eprintln!("{} : invalid --retention_max_bytes", name);
+            std::process::exit(1);
+        })
+    });
+
+    let durability = DurabilityMode::from_name(matches.get_one::<String>("durability").unwrap()).unwrap_or_else(|| {
+        eprintln!("{} : invalid --durability, expected none, batch, <N>ms or ack", name);
+        std::process::exit(1);
+    });
+
+    let gap_timeout = match matches.get_one::<String>("gap_timeout_ms").unwrap().parse::<u64>() {
+        Ok(0) => None,
+        Ok(ms) => Some(std::time::Duration::from_millis(ms)),
+        Err(_) => {
+            eprintln!("{} : invalid --gap_timeout_ms", name);
+            std::process::exit(1);
+        }
+    };
+
+    let text_template = match TextTemplate::parse(matches.get_one::<String>("text_template").unwrap()) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("{} : invalid --text_template - {}", name, e);
+            std::process::exit(1);
+        }
+    };
+
+    let text_fields = match LogField::parse_list(matches.get_one::<String>("text_fields").unwrap()) {
+        Ok(fields) => fields,
+        Err(e) => {
+            eprintln!("{} : invalid --text_fields - {}", name, e);
+            std::process::exit(1);
+        }
+    };
+
+    let writer_config = WriterConfig {
+        max_file_bytes,
+        backup_count,
+        rotation_interval,
+        compression,
+        retention_max_age,
+        retention_max_bytes,
+        output_format,
+        text_template,
+        text_fields,
+        resume_sequence,
+        durability,
+        gap_timeout,
+        ..WriterConfig::default()
+    };
     
     println!("{} : starting log server", name);
     if tcp_only {
@@ -43,7 +316,19 @@ fn main() {
     }
     
     // Run the server
-    if let Err(e) = run_server(name, host, port, grpc_port, tcp_only) {
+    let mut config = ServerConfig::new(name, host, port, grpc_port);
+    config.max_frame_bytes = max_frame_bytes;
+    config.tls = tls;
+    config.auth = auth;
+    config.udp_port = udp_port;
+    config.max_datagram_bytes = max_datagram_bytes;
+    config.http_port = http_port;
+    config.syslog_port = syslog_port;
+    config.forward_port = forward_port;
+    config.gelf_port = gelf_port;
+    config.unix_socket = unix_socket;
+    config.unix_socket_mode = unix_socket_mode;
+    if let Err(e) = run_server(config, writer_config, tcp_only) {
         eprintln!("{} : server failed - {}", name, e);
         std::process::exit(1);
     }
@@ -51,12 +336,24 @@ fn main() {
 
 //-----------------------------------------------------------------------------------------------
 
+/// Port of the `name` option, None when not given, prints an error and exits when not a port
+fn parse_port(matches: &ArgMatches, name: &str) -> Option<u16> {
+    matches.get_one::<String>(name).map(|port| {
+        port.parse::<u16>().unwrap_or_else(|_| {
+            eprintln!("{} : invalid --{}", matches.get_one::<String>("name").unwrap(), name);
+            std::process::exit(1);
+        })
+    })
+}
+
+//-----------------------------------------------------------------------------------------------
+
 /// Main server execution function
-fn run_server(name: &str, host: &str, port: u16, grpc_port: u16, tcp_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn run_server(config: ServerConfig, writer_config: WriterConfig, tcp_only: bool) -> Result<(), Box<dyn std::error::Error>> {
     let runtime = tokio::runtime::Runtime::new()?;
     
     runtime.block_on(async {
-        let server = LogServer::new(name, host, port, grpc_port, tcp_only).await?;
+        let server = LogServer::new(config, writer_config, tcp_only).await?;
         server.run().await
     })
 }
\ No newline at end of file
@@ -0,0 +1,408 @@
+//! Fluent Forward protocol server
+//!
+//! Receives MessagePack forward messages from Fluent Bit / Fluentd over TCP (TLS when configured),
+//! answers `{"ack": chunk}` once the records are written when the client asks for it. With token
+//! authentication the connection opens with the HELO / PING / PONG shared key handshake.
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use bytes::{Buf, BufMut, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use ring::digest::{digest, SHA512};
+use ring::rand::{SecureRandom, SystemRandom};
+use rmpv::Value;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::common::auth::{ClientIdentity, TokenAuth};
+use crate::common::config::ServerConfig;
+use crate::common::stats::{report_rejected, ServerStats};
+use crate::common::tls;
+use crate::core::forward::decode_forward;
+use crate::core::writers::WriterHandle;
+
+
+
+
+/// Bytes of the random nonce sent in HELO
+const NONCE_BYTES: usize = 16;
+
+//-----------------------------------------------------------------------------------------------
+
+/// Forward stream codec : one MessagePack value per item, back to back without framing
+///
+/// MessagePack has no length prefix : the value is scanned as its bytes arrive, keeping the scan
+/// position across reads, and only decoded once complete.
+#[derive(Clone, Debug)]
+pub struct ForwardCodec {
+    max_frame_bytes: usize,
+    /// Bytes of the buffered value already scanned
+    scanned: usize,
+    /// Values still to scan before the buffered value is complete (array items, map keys and values)
+    remaining: u64,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl ForwardCodec {
+    /// Create a codec rejecting messages larger than `max_frame_bytes`
+    pub fn new(max_frame_bytes: usize) -> Self {
+        Self {
+            max_frame_bytes,
+            scanned: 0,
+            remaining: 1,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Scan the buffered value further, true once it is complete
+    ///
+    /// Fails as soon as a declared length takes the value over `max_frame_bytes`.
+    fn scan(&mut self, src: &[u8]) -> io::Result<bool> {
+        while self.remaining > 0 {
+            let Some(&marker) = src.get(self.scanned) else {
+                return Ok(false);
+            };
+            // Header size, length field size, payload size, nested values
+            let (header, length_bytes, payload, nested) = match marker {
+                0x00..=0x7f | 0xc0 | 0xc2 | 0xc3 | 0xe0..=0xff => (1, 0, 0, 0),
+                0x80..=0x8f => (1, 0, 0, 2 * (marker & 0x0f) as u64),
+                0x90..=0x9f => (1, 0, 0, (marker & 0x0f) as u64),
+                0xa0..=0xbf => (1, 0, (marker & 0x1f) as usize, 0),
+                0xc4 | 0xd9 => (2, 1, 0, 0),
+                0xc5 | 0xda => (3, 2, 0, 0),
+                0xc6 | 0xdb => (5, 4, 0, 0),
+                0xc7 => (3, 1, 0, 0),
+                0xc8 => (4, 2, 0, 0),
+                0xc9 => (6, 4, 0, 0),
+                0xca => (5, 0, 0, 0),
+                0xcb => (9, 0, 0, 0),
+                0xcc | 0xd0 => (2, 0, 0, 0),
+                0xcd | 0xd1 => (3, 0, 0, 0),
+                0xce | 0xd2 => (5, 0, 0, 0),
+                0xcf | 0xd3 => (9, 0, 0, 0),
+                0xd4 => (3, 0, 0, 0),
+                0xd5 => (4, 0, 0, 0),
+                0xd6 => (6, 0, 0, 0),
+                0xd7 => (10, 0, 0, 0),
+                0xd8 => (18, 0, 0, 0),
+                0xdc => (3, 2, 0, 1),
+                0xdd => (5, 4, 0, 1),
+                0xde => (3, 2, 0, 2),
+                0xdf => (5, 4, 0, 2),
+                0xc1 => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid MessagePack marker 0xc1"));
+                }
+            };
+            let Some(header_bytes) = src.get(self.scanned..self.scanned + header) else {
+                return self.check_size(self.scanned + header).map(|_| false);
+            };
+
+            // Big endian length after the marker : a payload size, or an item count for arrays / maps
+            let (payload, nested) = if length_bytes > 0 {
+                let length = header_bytes[1..1 + length_bytes]
+                    .iter()
+                    .fold(0u64, |length, byte| (length << 8) | *byte as u64);
+                match nested {
+                    0 => (length as usize, 0),
+                    factor => (0, factor * length),
+                }
+            } else {
+                (payload, nested)
+            };
+
+            let end = self.scanned + header + payload;
+            self.check_size(end)?;
+            if end > src.len() {
+                return Ok(false);
+            }
+            self.scanned = end;
+            self.remaining += nested;
+            self.remaining -= 1;
+            // Every value still to scan takes at least one byte
+            self.check_size(self.scanned.saturating_add(self.remaining as usize))?;
+        }
+        Ok(true)
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Fail when the buffered value needs more than `max_frame_bytes`
+    fn check_size(&self, size: usize) -> io::Result<()> {
+        if size > self.max_frame_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("forward message exceeds the {} bytes limit", self.max_frame_bytes),
+            ));
+        }
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Decoder for ForwardCodec {
+    type Item = Value;
+    type Error = io::Error;
+
+    /// Decode one value, Ok(None) until it is completely buffered
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Value>> {
+        if !self.scan(src)? {
+            return Ok(None);
+        }
+        let length = self.scanned;
+        self.scanned = 0;
+        self.remaining = 1;
+
+        let mut data = &src[..length];
+        let value = rmpv::decode::read_value(&mut data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid MessagePack - {}", e)))?;
+        src.advance(length);
+        Ok(Some(value))
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Encoder<Value> for ForwardCodec {
+    type Error = io::Error;
+
+    /// Encode one value (ack, handshake reply)
+    fn encode(&mut self, item: Value, dst: &mut BytesMut) -> io::Result<()> {
+        rmpv::encode::write_value(&mut dst.writer(), &item).map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Fluent Forward protocol server
+pub struct ForwardServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl ForwardServer {
+    /// Create new forward server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Run the forward server
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(port) = self.config.forward_port else {
+            return Err("no forward port configured".into());
+        };
+        let addr = format!("{}:{}", self.config.host, port);
+        let listener = TcpListener::bind(&addr).await?;
+        let tls_acceptor = match &self.config.tls {
+            Some(tls) => Some(TlsAcceptor::from(tls.rustls_server_config()?)),
+            None => None,
+        };
+
+        println!(
+            "{} : forward server listening on {}{}",
+            self.config.name,
+            addr,
+            if tls_acceptor.is_some() { " (TLS)" } else { "" }
+        );
+
+        // Main server loop
+        loop {
+            let (socket, addr) = listener.accept().await?;
+            let connection = ForwardConnection {
+                config: self.config.clone(),
+                writer: self.writer.clone(),
+                stats: self.stats.clone(),
+                name: format!("{}_forward_{}", self.config.name, addr),
+                source: addr.to_string(),
+            };
+            let tls_acceptor = tls_acceptor.clone();
+
+            tokio::spawn(async move {
+                let result = match tls_acceptor {
+                    Some(tls_acceptor) => match tls::accept(&tls_acceptor, socket).await {
+                        // mTLS : the client certificate subject identifies the source
+                        Ok((tls_stream, subject)) => connection.handle(tls_stream, subject).await,
+                        Err(e) => Err(format!("TLS handshake failed - {}", e)),
+                    },
+                    None => connection.handle(socket, None).await,
+                };
+                if let Err(e) = result {
+                    eprintln!("{} : connection handler failed - {}", connection.name, e);
+                }
+            });
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// State of one forward connection
+struct ForwardConnection {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+    name: String,
+    source: String,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl ForwardConnection {
+    /// Authenticate the client if required, then record every forward message until it disconnects
+    async fn handle<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S, subject: Option<String>) -> Result<(), String> {
+        let source = subject.unwrap_or_else(|| self.source.clone());
+        let mut frames = Framed::new(stream, ForwardCodec::new(self.config.max_frame_bytes));
+        println!("{} : client connected", self.name);
+
+        let mut client = String::new();
+        if let Some(auth) = &self.config.auth {
+            let identity = self.handshake(&mut frames, auth).await?;
+            println!("{} : authenticated as {}", self.name, identity.0);
+            client = identity.0;
+        }
+
+        while let Some(frame) = frames.next().await {
+            let frame = frame.map_err(|e| format!("forward framing failed - {}", e))?;
+            // A malformed message is skipped without ack, the stream is still in sync
+            let message = match decode_forward(frame, &source, self.config.max_frame_bytes) {
+                Ok(message) => message,
+                Err(e) => {
+                    let detail = format!("forward message - {}", e);
+                    report_rejected(&self.name, &self.stats.malformed_forward_messages, 1, "malformed", &detail);
+                    continue;
+                }
+            };
+            if let Some(e) = message.skipped.first() {
+                let detail = format!("forward entries - {}", e);
+                let skipped = message.skipped.len() as u64;
+                report_rejected(&self.name, &self.stats.malformed_forward_messages, skipped, "skipped malformed", &detail);
+            }
+
+            let Some(chunk) = message.chunk else {
+                for mut record in message.records {
+                    record.client = client.clone();
+                    self.writer.send(record).await?;
+                }
+                continue;
+            };
+
+            // At-least-once : ack the chunk only once all its records reached the durability stage
+            let mut pending = Vec::with_capacity(message.records.len());
+            for mut record in message.records {
+                record.client = client.clone();
+                pending.push(self.writer.queue_acknowledged(record).await?);
+            }
+            for ack in pending {
+                ack.wait().await?;
+            }
+            let ack = Value::Map(vec![(Value::from("ack"), Value::from(chunk))]);
+            frames.send(ack).await.map_err(|e| format!("failed to send ack - {}", e))?;
+        }
+
+        println!("{} : client disconnected", self.name);
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Shared key handshake : HELO with a nonce, PING proving a token, PONG proving the server knows it
+    ///
+    /// The client digest is `sha512_hex(salt + client_hostname + nonce + shared_key)`, the server
+    /// answers with the same digest over its own hostname.
+    async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        frames: &mut Framed<S, ForwardCodec>,
+        auth: &TokenAuth,
+    ) -> Result<ClientIdentity, String> {
+        let mut nonce = [0u8; NONCE_BYTES];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "failed to generate a nonce".to_string())?;
+        let helo = Value::Array(vec![
+            Value::from("HELO"),
+            Value::Map(vec![
+                (Value::from("nonce"), Value::Binary(nonce.to_vec())),
+                (Value::from("auth"), Value::from("")),
+                (Value::from("keepalive"), Value::from(true)),
+            ]),
+        ]);
+        frames.send(helo).await.map_err(|e| format!("failed to send HELO - {}", e))?;
+
+        let ping = match frames.next().await {
+            Some(Ok(ping)) => ping,
+            Some(Err(e)) => return Err(format!("forward framing failed - {}", e)),
+            None => return Err("connection closed during the handshake".to_string()),
+        };
+        // ["PING", client_hostname, shared_key_salt, shared_key_digest, username, password]
+        let ping = match ping {
+            Value::Array(items) if items.len() == 6 && items[0].as_str() == Some("PING") => items,
+            _ => return Err("authentication required, first message is not a PING".to_string()),
+        };
+        let (client_hostname, salt, client_digest) = (bytes_of(&ping[1]), bytes_of(&ping[2]), ping[3].as_str().unwrap_or(""));
+
+        let identity = auth.identify_digest(client_digest, |key| {
+            shared_key_digest(&salt, &client_hostname, &nonce, key)
+        });
+        let Some((identity, key)) = identity else {
+            let failures = self.stats.auth_failures.fetch_add(1, Ordering::Relaxed) + 1;
+            let pong = Value::Array(vec![
+                Value::from("PONG"),
+                Value::from(false),
+                Value::from("shared key mismatch"),
+                Value::from(""),
+                Value::from(""),
+            ]);
+            let _ = frames.send(pong).await;
+            return Err(format!("authentication failed ({} failures in total)", failures));
+        };
+
+        let server_digest = shared_key_digest(&salt, self.config.name.as_bytes(), &nonce, &key);
+        let pong = Value::Array(vec![
+            Value::from("PONG"),
+            Value::from(true),
+            Value::from(""),
+            Value::from(self.config.name.as_str()),
+            Value::from(server_digest),
+        ]);
+        frames.send(pong).await.map_err(|e| format!("failed to send PONG - {}", e))?;
+        Ok(identity)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Raw bytes of a string or binary handshake field
+fn bytes_of(value: &Value) -> Vec<u8> {
+    match value {
+        Value::String(text) => text.as_bytes().to_vec(),
+        Value::Binary(bytes) => bytes.clone(),
+        _ => Vec::new(),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Lower case hex SHA-512 of `salt + hostname + nonce + shared_key`
+fn shared_key_digest(salt: &[u8], hostname: &[u8], nonce: &[u8], shared_key: &str) -> String {
+    let input = [salt, hostname, nonce, shared_key.as_bytes()].concat();
+    digest(&SHA512, &input)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,296 @@
+//! GELF server over UDP and TCP
+//!
+//! UDP carries one message per datagram, or a chunked message reassembled from up to 128 chunks,
+//! zlib / gzip compressed or not. TCP carries uncompressed messages delimited by a null byte.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use tokio::net::{TcpListener, UdpSocket};
+use tokio_util::codec::{AnyDelimiterCodec, FramedRead};
+
+use crate::common::config::{ServerConfig, DEFAULT_MAX_DATAGRAM_BYTES};
+use crate::common::stats::{report_rejected, ServerStats};
+use crate::core::gelf::parse_gelf;
+use crate::core::writers::WriterHandle;
+
+
+
+
+/// First bytes of a chunked GELF datagram
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+
+/// Chunk header : magic, message id (8 bytes), sequence number, sequence count
+const CHUNK_HEADER_BYTES: usize = 12;
+
+/// Most chunks of one message
+const MAX_CHUNKS: u8 = 128;
+
+/// Time allowed for every chunk of a message to arrive, the message is dropped after it
+pub const CHUNK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Chunked messages reassembled at the same time, chunks of further messages are rejected
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// Chunk bytes held for all messages being reassembled, chunks beyond it are rejected
+pub const MAX_PENDING_BYTES: usize = 64 * 1024 * 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+/// Reassembly of chunked GELF datagrams
+#[derive(Debug)]
+pub struct GelfChunks {
+    max_message_bytes: usize,
+    max_pending_bytes: usize,
+    pending_bytes: usize,
+    pending: HashMap<[u8; 8], PendingMessage>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Chunks received so far of one message
+#[derive(Debug)]
+struct PendingMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+    started: Instant,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl GelfChunks {
+    /// Create a reassembler rejecting messages larger than `max_message_bytes`, and chunks once
+    /// the messages being reassembled hold `max_pending_bytes`
+    pub fn new(max_message_bytes: usize, max_pending_bytes: usize) -> Self {
+        Self {
+            max_message_bytes,
+            max_pending_bytes,
+            pending_bytes: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Add one datagram received at `now`, returns the payload once the message is complete
+    ///
+    /// A datagram without the chunk magic is a complete message by itself. Chunks may arrive in
+    /// any order, a duplicated chunk is ignored. A message that cannot complete within the
+    /// limits is dropped with the chunk that exceeded them.
+    pub fn add(&mut self, datagram: &[u8], now: Instant) -> Result<Option<Vec<u8>>, String> {
+        if !datagram.starts_with(&CHUNK_MAGIC) {
+            return Ok(Some(datagram.to_vec()));
+        }
+        if datagram.len() < CHUNK_HEADER_BYTES {
+            return Err("truncated GELF chunk header".to_string());
+        }
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&datagram[2..10]);
+        let (sequence, count) = (datagram[10], datagram[11]);
+        if count == 0 || count > MAX_CHUNKS || sequence >= count {
+            return Err(format!("invalid GELF chunk {} of {}", sequence, count));
+        }
+        let chunk = &datagram[CHUNK_HEADER_BYTES..];
+        if count == 1 {
+            return Ok(Some(chunk.to_vec()));
+        }
+
+        if !self.pending.contains_key(&id) && self.pending.len() >= MAX_PENDING_MESSAGES {
+            return Err(format!("more than {} chunked GELF messages in progress", MAX_PENDING_MESSAGES));
+        }
+        let message = self.pending.entry(id).or_insert_with(|| PendingMessage {
+            chunks: vec![None; count as usize],
+            received: 0,
+            bytes: 0,
+            started: now,
+        });
+        if message.chunks.len() != count as usize {
+            return Err(format!("GELF chunk count {} differs from {} of earlier chunks", count, message.chunks.len()));
+        }
+
+        let slot = &mut message.chunks[sequence as usize];
+        if slot.is_none() {
+            if message.bytes + chunk.len() > self.max_message_bytes {
+                self.remove(&id);
+                return Err(format!("chunked GELF message exceeds the {} bytes limit", self.max_message_bytes));
+            }
+            if self.pending_bytes + chunk.len() > self.max_pending_bytes {
+                self.remove(&id);
+                return Err(format!("chunked GELF messages in progress exceed the {} bytes limit", self.max_pending_bytes));
+            }
+            message.bytes += chunk.len();
+            self.pending_bytes += chunk.len();
+            *slot = Some(chunk.to_vec());
+            message.received += 1;
+        }
+        if message.received < message.chunks.len() {
+            return Ok(None);
+        }
+
+        let Some(message) = self.remove(&id) else {
+            return Ok(None);
+        };
+        Ok(Some(message.chunks.into_iter().flatten().flatten().collect()))
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Drop the messages still missing chunks `CHUNK_TIMEOUT` after their first one, returns how many
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let before = self.pending.len();
+        let pending_bytes = &mut self.pending_bytes;
+        self.pending.retain(|_, message| {
+            let complete_in_time = now.duration_since(message.started) < CHUNK_TIMEOUT;
+            if !complete_in_time {
+                *pending_bytes -= message.bytes;
+            }
+            complete_in_time
+        });
+        before - self.pending.len()
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Chunk bytes held for the messages being reassembled
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Stop reassembling message `id`, releasing its chunk bytes
+    fn remove(&mut self, id: &[u8; 8]) -> Option<PendingMessage> {
+        let message = self.pending.remove(id)?;
+        self.pending_bytes -= message.bytes;
+        Some(message)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// GELF server, listening on the same port over UDP and TCP
+pub struct GelfServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl GelfServer {
+    /// Create new GELF server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Run the GELF server, UDP and TCP
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(port) = self.config.gelf_port else {
+            return Err("no GELF port configured".into());
+        };
+        let addr = format!("{}:{}", self.config.host, port);
+        let udp_socket = UdpSocket::bind(&addr).await?;
+        let tcp_listener = TcpListener::bind(&addr).await?;
+
+        println!("{} : GELF server listening on {} (UDP and TCP)", self.config.name, addr);
+
+        tokio::try_join!(self.run_udp(udp_socket), self.run_tcp(tcp_listener))?;
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Datagrams and chunked messages, never waiting for the writer
+    async fn run_udp(&self, socket: UdpSocket) -> io::Result<()> {
+        let mut buffer = vec![0u8; DEFAULT_MAX_DATAGRAM_BYTES];
+        let mut chunks = GelfChunks::new(self.config.max_frame_bytes, MAX_PENDING_BYTES);
+        let mut expiry = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                received = socket.recv_from(&mut buffer) => {
+                    let (size, addr) = received?;
+                    let payload = match chunks.add(&buffer[..size], Instant::now()) {
+                        Ok(Some(payload)) => payload,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            report_rejected(&self.config.name, &self.stats.malformed_gelf_messages, 1, "malformed", &format!("GELF datagram from {} - {}", addr, e));
+                            continue;
+                        }
+                    };
+                    let record = match parse_gelf(&payload, &addr.to_string(), self.config.max_frame_bytes) {
+                        Ok(record) => record,
+                        Err(e) => {
+                            report_rejected(&self.config.name, &self.stats.malformed_gelf_messages, 1, "malformed", &format!("GELF message from {} - {}", addr, e));
+                            continue;
+                        }
+                    };
+                    if let Err(e) = self.writer.try_send(record) {
+                        report_rejected(&self.config.name, &self.stats.dropped_gelf_messages, 1, "dropped", &format!("GELF message from {} - {}", addr, e));
+                    }
+                }
+                _ = expiry.tick() => {
+                    let expired = chunks.expire(Instant::now()) as u64;
+                    if expired > 0 {
+                        report_rejected(&self.config.name, &self.stats.incomplete_gelf_messages, expired, "incomplete", "chunked GELF messages, chunks missing after 5s");
+                    }
+                }
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Null-delimited messages, one task per connection
+    async fn run_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (socket, addr) = listener.accept().await?;
+            let name = format!("{}_gelf_{}", self.config.name, addr);
+            let source = addr.to_string();
+            let max_frame_bytes = self.config.max_frame_bytes;
+            let codec = AnyDelimiterCodec::new_with_max_length(vec![b'\0'], Vec::new(), max_frame_bytes);
+            let mut frames = FramedRead::new(socket, codec);
+            let writer = self.writer.clone();
+            let stats = self.stats.clone();
+
+            tokio::spawn(async move {
+                println!("{} : client connected", name);
+                while let Some(frame) = frames.next().await {
+                    let frame = match frame {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            eprintln!("{} : GELF framing failed - {}", name, e);
+                            break;
+                        }
+                    };
+                    // Some senders follow the null byte with a line end
+                    if frame.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    // A malformed message is skipped, the framing is still in sync
+                    let record = match parse_gelf(&frame, &source, max_frame_bytes) {
+                        Ok(record) => record,
+                        Err(e) => {
+                            report_rejected(&name, &stats.malformed_gelf_messages, 1, "malformed", &format!("GELF message - {}", e));
+                            continue;
+                        }
+                    };
+                    if let Err(e) = writer.send(record).await {
+                        eprintln!("{} : message handling failed - {}", name, e);
+                        break;
+                    }
+                }
+                println!("{} : client disconnected", name);
+            });
+        }
+    }
+}
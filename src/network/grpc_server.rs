@@ -2,41 +2,90 @@
 //!
 //! Provides gRPC endpoint for receiving log messages alongside TCP socket.
 
-use tonic::{transport::Server, Request, Response, Status};
-use tokio::sync::mpsc;
 use std::sync::Arc;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
+use futures_util::stream::FuturesOrdered;
+use futures_util::StreamExt;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+
+use crate::common::auth::{ClientIdentity, TokenAuth};
 use crate::common::config::ServerConfig;
-use crate::core::writers::LogWriter;
-use crate::core::handlers::handle_grpc_message;
+use crate::common::stats::ServerStats;
+use crate::common::tls::certificate_subject;
+use crate::core::writers::{PendingAck, WriterHandle};
+use crate::core::handlers::{decode_grpc_message, handle_grpc_message, queue_grpc_message};
+use crate::core::otlp::records_from_otlp;
 
 // Add this line - it includes the generated gRPC code
 pub mod log_service {
     tonic::include_proto!("logservice");
 }
 
+// OTLP logs service, nested like the proto packages so the generated cross references resolve
+pub mod opentelemetry {
+    pub mod proto {
+        pub mod common {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.common.v1");
+            }
+        }
+        pub mod resource {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.resource.v1");
+            }
+        }
+        pub mod logs {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.logs.v1");
+            }
+        }
+        pub mod collector {
+            pub mod logs {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.collector.logs.v1");
+                }
+            }
+        }
+    }
+}
+
+use opentelemetry::proto::collector::logs::v1::{
+    logs_service_server::{LogsService, LogsServiceServer},
+    ExportLogsPartialSuccess,
+    ExportLogsServiceRequest,
+    ExportLogsServiceResponse,
+};
+
 // Import the generated types
 use log_service::{
     log_service_server::{LogService, LogServiceServer},
     LogRequest as ProtoLogRequest,  // Rename the imported type
-    LogResponse
+    LogResponse,
+    LogBatchRequest,
+    LogBatchResponse,
+    LogError,
 };
 
+/// Rejected messages detailed in a batch or stream summary
+const MAX_REPORTED_ERRORS: usize = 100;
+
 /// gRPC server for log messages
 pub struct GrpcServer {
     config: ServerConfig,
-    writer: Arc<LogWriter>,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
 }
 
 //-----------------------------------------------------------------------------------------------
 
 impl GrpcServer {
     /// Create new gRPC server
-    pub fn new(config: &ServerConfig, writer: Arc<LogWriter>) -> Self {
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
         Self {
             config: config.clone(),
             writer,
+            stats,
         }
     }
     
@@ -46,11 +95,24 @@ impl GrpcServer {
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         let addr = format!("{}:{}", self.config.host, self.config.grpc_port).parse()?;
         let service = GrpcLogServiceImpl::new(&self.config, self.writer.clone());
+        let otlp_service = OtlpLogsServiceImpl::new(&self.config, self.writer.clone());
+        let interceptor = authenticator(&self.config, self.stats.clone());
         
-        println!("{} : gRPC server listening on {}", self.config.name, addr);
+        let mut builder = Server::builder();
+        if let Some(tls) = &self.config.tls {
+            builder = builder.tls_config(tls.tonic_server_config()?)?;
+        }
+        
+        println!(
+            "{} : gRPC server listening on {}{}",
+            self.config.name,
+            addr,
+            if self.config.tls.is_some() { " (TLS)" } else { "" }
+        );
         
-        Server::builder()
-            .add_service(LogServiceServer::new(service))
+        builder
+            .add_service(LogServiceServer::with_interceptor(service, interceptor.clone()))
+            .add_service(LogsServiceServer::with_interceptor(otlp_service, interceptor))
             .serve(addr)
             .await?;
             
@@ -62,8 +124,7 @@ impl GrpcServer {
 
 /// gRPC service implementation
 pub struct GrpcLogServiceImpl {
-    writer_tx: mpsc::Sender<String>,
-    sequence_counter: Arc<AtomicU64>,
+    writer: WriterHandle,
     name: String,
 }
 
@@ -71,13 +132,9 @@ pub struct GrpcLogServiceImpl {
 
 impl GrpcLogServiceImpl {
     /// Create new gRPC service implementation
-    pub fn new(config: &ServerConfig, writer: Arc<LogWriter>) -> Self {
-        let writer_tx = writer.start_writer_task();
-        let sequence_counter = Arc::new(AtomicU64::new(0));
-        
+    pub fn new(config: &ServerConfig, writer: WriterHandle) -> Self {
         Self {
-            writer_tx,
-            sequence_counter,
+            writer,
             name: config.name.clone(),
         }
     }
@@ -92,11 +149,17 @@ impl LogService for GrpcLogServiceImpl {
         &self,
         request: Request<ProtoLogRequest>,  // Use the renamed type
     ) -> Result<Response<LogResponse>, Status> {
+        let source = remote_source(&request);
+        let client = request_client(&request);
         let log_data = request.into_inner();
-        
-        // Convert to internal type and handle
-        let internal_request = InternalLogRequest::from(log_data);  // Use the new name
-        match handle_grpc_message(internal_request, self.writer_tx.clone(), self.sequence_counter.clone()).await {
+
+        // A message the client got wrong is its error, not the server's
+        let record = decode_grpc_message(log_data, &source, &client).map_err(|e| {
+            eprintln!("{} : invalid gRPC message - {}", self.name, e);
+            Status::invalid_argument(e)
+        })?;
+
+        match handle_grpc_message(record, &self.writer).await {
             Ok(_) => {
                 Ok(Response::new(LogResponse { success: true }))
             }
@@ -106,36 +169,248 @@ impl LogService for GrpcLogServiceImpl {
             }
         }
     }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Handle a batch of log messages, written in request order
+    async fn log_batch(
+        &self,
+        request: Request<LogBatchRequest>,
+    ) -> Result<Response<LogBatchResponse>, Status> {
+        let source = remote_source(&request);
+        let client = request_client(&request);
+        let requests = request.into_inner().requests;
+
+        // Queue everything first, then wait for the acknowledgements : one round trip for the batch
+        let mut summary = BatchSummary::default();
+        let mut pending = FuturesOrdered::new();
+        for (index, log_request) in (0u64..).zip(requests) {
+            match queue_grpc_message(log_request, &self.writer, &source, &client).await {
+                Ok(ack) => pending.push_back(wait_acknowledged(index, ack)),
+                Err(e) => summary.add(index, Err(e)),
+            }
+        }
+        while let Some((index, result)) = pending.next().await {
+            summary.add(index, result);
+        }
+
+        Ok(Response::new(summary.into_response(&self.name)))
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Handle a client stream of log messages, summarized once the client closes the stream
+    async fn stream_logs(
+        &self,
+        request: Request<Streaming<ProtoLogRequest>>,
+    ) -> Result<Response<LogBatchResponse>, Status> {
+        let source = remote_source(&request);
+        let client = request_client(&request);
+        let mut stream = request.into_inner();
+
+        // Messages are queued as they arrive, acknowledgements are collected meanwhile
+        let mut summary = BatchSummary::default();
+        let mut pending = FuturesOrdered::new();
+        let mut index = 0u64;
+        loop {
+            tokio::select! {
+                message = stream.message() => match message? {
+                    Some(log_request) => {
+                        match queue_grpc_message(log_request, &self.writer, &source, &client).await {
+                            Ok(ack) => pending.push_back(wait_acknowledged(index, ack)),
+                            Err(e) => summary.add(index, Err(e)),
+                        }
+                        index += 1;
+                    }
+                    None => break,
+                },
+                Some((index, result)) = pending.next() => summary.add(index, result),
+            }
+        }
+        while let Some((index, result)) = pending.next().await {
+            summary.add(index, result);
+        }
+
+        Ok(Response::new(summary.into_response(&self.name)))
+    }
 }
 
 //-----------------------------------------------------------------------------------------------
 
-/// Internal log request wrapper for internal use
-pub struct InternalLogRequest {  // Renamed to avoid conflict
-    pub timestamp: String,
-    pub hostname: String,
-    pub logger_name: String,
-    pub level: i32,
-    pub filename: String,
-    pub function_name: String,
-    pub line_number: String,
-    pub message: String,
+/// OTLP `LogsService` implementation, for OpenTelemetry SDKs and collectors
+pub struct OtlpLogsServiceImpl {
+    writer: WriterHandle,
+    name: String,
 }
 
 //-----------------------------------------------------------------------------------------------
 
-// Conversion from protobuf to internal type
-impl From<ProtoLogRequest> for InternalLogRequest {  // Use the renamed types
-    fn from(request: ProtoLogRequest) -> Self {
+impl OtlpLogsServiceImpl {
+    /// Create new OTLP logs service implementation
+    pub fn new(config: &ServerConfig, writer: WriterHandle) -> Self {
         Self {
-            timestamp: request.timestamp,
-            hostname: request.hostname,
-            logger_name: request.logger_name,
-            level: request.level,
-            filename: request.filename,
-            function_name: request.function_name,
-            line_number: request.line_number,
-            message: request.message,
+            writer,
+            name: config.name.clone(),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tonic::async_trait]
+impl LogsService for OtlpLogsServiceImpl {
+    /// Handle an OTLP export, answered once every record reached the durability stage
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        let source = remote_source(&request);
+        let client = request_client(&request);
+        let records = request
+            .into_inner()
+            .resource_logs
+            .into_iter()
+            .flat_map(|resource_logs| records_from_otlp(resource_logs, &source));
+
+        let mut summary = BatchSummary::default();
+        let mut pending = FuturesOrdered::new();
+        for (index, record) in (0u64..).zip(records) {
+            let mut record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    summary.add(index, Err(e));
+                    continue;
+                }
+            };
+            record.client = client.clone();
+            match self.writer.queue_acknowledged(record).await {
+                Ok(ack) => pending.push_back(wait_acknowledged(index, ack)),
+                Err(e) => summary.add(index, Err(e)),
+            }
+        }
+        while let Some((index, result)) = pending.next().await {
+            summary.add(index, result);
+        }
+
+        // OTLP partial success : only set when records were rejected
+        let summary = summary.into_response(&self.name);
+        let partial_success = (summary.rejected > 0).then(|| ExportLogsPartialSuccess {
+            rejected_log_records: summary.rejected as i64,
+            error_message: summary.errors.first().map(|error| error.error.clone()).unwrap_or_default(),
+        });
+        Ok(Response::new(ExportLogsServiceResponse { partial_success }))
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Accepted / rejected counts of a batch or stream, also summarizes HTTP requests
+#[derive(Default)]
+pub(crate) struct BatchSummary {
+    accepted: u64,
+    rejected: u64,
+    errors: Vec<LogError>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl BatchSummary {
+    /// Count the outcome of the message at `index`
+    pub(crate) fn add(&mut self, index: u64, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.accepted += 1,
+            Err(error) => {
+                self.rejected += 1;
+                if self.errors.len() < MAX_REPORTED_ERRORS {
+                    self.errors.push(LogError { index, error });
+                }
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Build the summary response
+    pub(crate) fn into_response(mut self, name: &str) -> LogBatchResponse {
+        if self.rejected > 0 {
+            eprintln!("{} : {} of {} messages rejected", name, self.rejected, self.accepted + self.rejected);
+        }
+        self.errors.sort_by_key(|error| error.index);
+        LogBatchResponse {
+            success: self.rejected == 0,
+            accepted: self.accepted,
+            rejected: self.rejected,
+            errors: self.errors,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Wait for the acknowledgement of the message at `index`
+pub(crate) async fn wait_acknowledged(index: u64, ack: PendingAck) -> (u64, Result<(), String>) {
+    (index, ack.wait().await.map(|_| ()))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Record source : the client certificate subject with mTLS, else the remote address of the client
+fn remote_source<T>(request: &Request<T>) -> String {
+    if let Some(subject) = request
+        .peer_certs()
+        .and_then(|certs| certs.first().and_then(|cert| certificate_subject(cert.get_ref())))
+    {
+        return subject;
+    }
+    request
+        .remote_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|| "grpc".to_string())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Interceptor checking the bearer token of every request when token authentication is enabled
+///
+/// The client identity is passed to the service in the request extensions.
+// tonic interceptors return `Status` unboxed
+#[allow(clippy::result_large_err)]
+fn authenticator(
+    config: &ServerConfig,
+    stats: Arc<ServerStats>,
+) -> impl Fn(Request<()>) -> Result<Request<()>, Status> + Clone {
+    let auth: Option<Arc<TokenAuth>> = config.auth.clone();
+    let name = config.name.clone();
+    move |mut request: Request<()>| {
+        let Some(auth) = &auth else {
+            return Ok(request);
+        };
+        let identity = request
+            .metadata()
+            .get("authorization")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| auth.identify_bearer(header));
+        match identity {
+            Some(identity) => {
+                request.extensions_mut().insert(identity);
+                Ok(request)
+            }
+            None => {
+                let failures = stats.auth_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                eprintln!("{} : gRPC authentication failed ({} failures in total)", name, failures);
+                Err(Status::unauthenticated("missing or invalid bearer token"))
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Record client identity set by the interceptor, empty without token authentication
+fn request_client<T>(request: &Request<T>) -> String {
+    request
+        .extensions()
+        .get::<ClientIdentity>()
+        .map(|identity| identity.0.clone())
+        .unwrap_or_default()
+}
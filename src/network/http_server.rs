@@ -0,0 +1,277 @@
+//! HTTP server for JSON log messages
+//!
+//! `POST /v1/logs` takes one JSON object, an array of objects or NDJSON, with the `LogRequest`
+//! field names, and answers with the accepted / rejected counts and per-item errors.
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use futures_util::stream::FuturesOrdered;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+use crate::common::config::ServerConfig;
+use crate::common::stats::ServerStats;
+use crate::common::tls;
+use crate::core::handlers::queue_json_message;
+use crate::core::writers::WriterHandle;
+use crate::network::grpc_server::{wait_acknowledged, BatchSummary};
+
+
+
+
+/// Content type forcing one JSON object per line
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+//-----------------------------------------------------------------------------------------------
+
+/// HTTP server for JSON log messages
+pub struct HttpServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// State shared by the HTTP handlers
+struct HttpState {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Record source of the connection a request came from
+#[derive(Clone)]
+struct Peer(String);
+
+//-----------------------------------------------------------------------------------------------
+
+impl HttpServer {
+    /// Create new HTTP server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Run the HTTP server
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(port) = self.config.http_port else {
+            return Err("no HTTP port configured".into());
+        };
+        let addr = format!("{}:{}", self.config.host, port);
+        let listener = TcpListener::bind(&addr).await?;
+        self.serve(listener).await
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Serve the connections of a bound listener
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), Box<dyn std::error::Error>> {
+        let addr = listener.local_addr()?;
+        let tls_acceptor = match &self.config.tls {
+            Some(tls) => Some(TlsAcceptor::from(tls.rustls_server_config()?)),
+            None => None,
+        };
+
+        let state = Arc::new(HttpState {
+            config: self.config.clone(),
+            writer: self.writer.clone(),
+            stats: self.stats.clone(),
+        });
+        let router = Router::new()
+            .route("/v1/logs", post(post_logs).options(preflight))
+            .layer(DefaultBodyLimit::max(self.config.max_frame_bytes))
+            .with_state(state);
+
+        println!(
+            "{} : HTTP server listening on {}{}",
+            self.config.name,
+            addr,
+            if tls_acceptor.is_some() { " (TLS)" } else { "" }
+        );
+
+        // Main server loop
+        loop {
+            let (socket, addr) = listener.accept().await?;
+            let name = format!("{}_http_{}", self.config.name, addr);
+            let router = router.clone();
+            let tls_acceptor = tls_acceptor.clone();
+
+            tokio::spawn(async move {
+                let result = match tls_acceptor {
+                    Some(tls_acceptor) => match tls::accept(&tls_acceptor, socket).await {
+                        Ok((tls_stream, subject)) => {
+                            // mTLS : the client certificate subject identifies the source
+                            let source = subject.unwrap_or_else(|| addr.to_string());
+                            serve_connection(tls_stream, router, source).await
+                        }
+                        Err(e) => Err(io::Error::new(e.kind(), format!("TLS handshake failed - {}", e))),
+                    },
+                    None => serve_connection(socket, router, addr.to_string()).await,
+                };
+                if let Err(e) = result {
+                    eprintln!("{} : connection handler failed - {}", name, e);
+                }
+            });
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Serve the requests of one HTTP/1.1 connection
+async fn serve_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    stream: S,
+    router: Router,
+    source: String,
+) -> io::Result<()> {
+    hyper::server::conn::Http::new()
+        .http1_only(true)
+        .serve_connection(stream, router.layer(Extension(Peer(source))))
+        .await
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// `POST /v1/logs` : queue every item, then answer once all of them are acknowledged
+async fn post_logs(
+    State(state): State<Arc<HttpState>>,
+    Extension(peer): Extension<Peer>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // Token authentication : same bearer header as gRPC
+    let mut client = String::new();
+    if let Some(auth) = &state.config.auth {
+        let identity = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| auth.identify_bearer(value));
+        match identity {
+            Some(identity) => client = identity.0,
+            None => {
+                let failures = state.stats.auth_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                eprintln!("{} : HTTP authentication failed ({} failures in total)", state.config.name, failures);
+                return error_response(StatusCode::UNAUTHORIZED, "missing or invalid bearer token");
+            }
+        }
+    }
+
+    let ndjson = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(NDJSON_CONTENT_TYPE));
+    let items = match json_items(&body, ndjson) {
+        Ok(items) => items,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+    };
+
+    let mut summary = BatchSummary::default();
+    let mut pending = FuturesOrdered::new();
+    for (index, item) in (0u64..).zip(items) {
+        let queued = match item {
+            Ok(item) => queue_json_message(&item, &state.writer, &peer.0, &client).await,
+            Err(e) => Err(e),
+        };
+        match queued {
+            Ok(ack) => pending.push_back(wait_acknowledged(index, ack)),
+            Err(e) => summary.add(index, Err(e)),
+        }
+    }
+    while let Some((index, result)) = pending.next().await {
+        summary.add(index, result);
+    }
+
+    let summary = summary.into_response(&state.config.name);
+    let errors: Vec<Value> = summary
+        .errors
+        .iter()
+        .map(|error| json!({ "index": error.index, "error": error.error }))
+        .collect();
+    let body = json!({
+        "success": summary.success,
+        "accepted": summary.accepted,
+        "rejected": summary.rejected,
+        "errors": errors,
+    });
+    with_cors((StatusCode::OK, Json(body)).into_response())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// CORS preflight, so browser apps on other origins can post logs
+async fn preflight() -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("POST"));
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("authorization, content-type"),
+    );
+    with_cors(response)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Split the body into items : one object, an array of objects, or one object per line
+///
+/// A body that is not a single JSON value is read as NDJSON, a line that does not parse only
+/// rejects its own item.
+fn json_items(body: &[u8], ndjson: bool) -> Result<Vec<Result<Value, String>>, String> {
+    let text = std::str::from_utf8(body).map_err(|_| "request body is not UTF-8".to_string())?;
+    if text.trim().is_empty() {
+        return Err("empty request body".to_string());
+    }
+
+    if !ndjson {
+        match serde_json::from_str::<Value>(text) {
+            Ok(Value::Array(items)) => return Ok(items.into_iter().map(Ok).collect()),
+            Ok(item @ Value::Object(_)) => return Ok(vec![Ok(item)]),
+            Ok(_) => return Err("expected a JSON object or an array of objects".to_string()),
+            Err(e) if text.trim().lines().count() == 1 => return Err(format!("invalid JSON - {}", e)),
+            Err(_) => {}
+        }
+    }
+
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str::<Value>(line).map_err(|e| format!("invalid JSON - {}", e)))
+        .collect())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Error answer for a request that was not processed at all
+fn error_response(status: StatusCode, error: &str) -> Response {
+    with_cors((status, Json(json!({ "success": false, "error": error }))).into_response())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Allow any origin : access is controlled by the bearer token, not by cookies
+fn with_cors(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
@@ -1,4 +1,10 @@
 //! Network communication layers
 
 pub mod tcp_server;
-pub mod grpc_server;
\ No newline at end of file
+pub mod grpc_server;
+pub mod unix_server;
+pub mod udp_server;
+pub mod syslog_server;
+pub mod http_server;
+pub mod forward_server;
+pub mod gelf_server;
\ No newline at end of file
@@ -0,0 +1,229 @@
+//! Syslog server over UDP and TCP
+//!
+//! UDP carries one message per datagram. TCP uses octet-counting framing (RFC 6587 section
+//! 3.4.1, `<length> <message>`), or newline-delimited messages for senders that only do that.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::{Buf, BytesMut};
+use futures_util::StreamExt;
+use tokio::net::{TcpListener, UdpSocket};
+use tokio_util::codec::{Decoder, FramedRead};
+
+use crate::common::config::{ServerConfig, DEFAULT_MAX_DATAGRAM_BYTES};
+use crate::common::stats::{report_rejected, ServerStats};
+use crate::core::syslog::parse_syslog;
+use crate::core::writers::WriterHandle;
+
+
+
+
+/// Digits of an octet count, enough for any frame below 4 GiB
+const MAX_LENGTH_DIGITS: usize = 10;
+
+/// Syslog TCP frame codec : octet-counted when the frame starts with a digit, else up to LF
+#[derive(Clone, Copy, Debug)]
+pub struct SyslogCodec {
+    max_frame_bytes: usize,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl SyslogCodec {
+    /// Create a codec rejecting messages larger than `max_frame_bytes`
+    pub fn new(max_frame_bytes: usize) -> Self {
+        Self { max_frame_bytes }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Error of a frame that cannot be read, the stream cannot be resynchronized after it
+    fn invalid(message: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Decoder for SyslogCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    /// Decode one message, Ok(None) until it is completely buffered
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        // Stray line ends between frames are not messages
+        let blank = src.iter().take_while(|byte| matches!(byte, b'\r' | b'\n' | b'\0')).count();
+        src.advance(blank);
+        let Some(&first) = src.first() else {
+            return Ok(None);
+        };
+
+        if first.is_ascii_digit() {
+            // Octet counting : MSG-LEN SP SYSLOG-MSG
+            let Some(space) = src.iter().take(MAX_LENGTH_DIGITS + 1).position(|byte| *byte == b' ') else {
+                if src.len() > MAX_LENGTH_DIGITS {
+                    return Err(Self::invalid("invalid syslog octet count".to_string()));
+                }
+                return Ok(None);
+            };
+            let length = std::str::from_utf8(&src[..space])
+                .ok()
+                .and_then(|digits| digits.parse::<usize>().ok())
+                .ok_or_else(|| Self::invalid("invalid syslog octet count".to_string()))?;
+            if length > self.max_frame_bytes {
+                return Err(Self::invalid(format!(
+                    "syslog message of {} bytes exceeds the {} bytes limit",
+                    length, self.max_frame_bytes
+                )));
+            }
+            if src.len() < space + 1 + length {
+                src.reserve(space + 1 + length - src.len());
+                return Ok(None);
+            }
+            src.advance(space + 1);
+            return Ok(Some(src.split_to(length)));
+        }
+
+        // Non-transparent framing : the message ends at LF (or NUL)
+        match src.iter().position(|byte| matches!(byte, b'\n' | b'\0')) {
+            Some(end) if end > self.max_frame_bytes => Err(Self::invalid(format!(
+                "syslog message exceeds the {} bytes limit",
+                self.max_frame_bytes
+            ))),
+            Some(end) => {
+                let message = src.split_to(end);
+                src.advance(1);
+                Ok(Some(message))
+            }
+            None if src.len() > self.max_frame_bytes => Err(Self::invalid(format!(
+                "syslog message exceeds the {} bytes limit",
+                self.max_frame_bytes
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// A newline-delimited sender may close the connection without a final LF
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if let Some(message) = self.decode(src)? {
+            return Ok(Some(message));
+        }
+        match src.first() {
+            None => Ok(None),
+            Some(first) if !first.is_ascii_digit() => Ok(Some(src.split())),
+            Some(_) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed mid-message")),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Syslog server, listening on the same port over UDP and TCP
+pub struct SyslogServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl SyslogServer {
+    /// Create new syslog server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Run the syslog server, UDP and TCP
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(port) = self.config.syslog_port else {
+            return Err("no syslog port configured".into());
+        };
+        let addr = format!("{}:{}", self.config.host, port);
+        let udp_socket = UdpSocket::bind(&addr).await?;
+        let tcp_listener = TcpListener::bind(&addr).await?;
+
+        println!("{} : syslog server listening on {} (UDP and TCP)", self.config.name, addr);
+
+        tokio::try_join!(self.run_udp(udp_socket), self.run_tcp(tcp_listener))?;
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// One syslog message per datagram
+    async fn run_udp(&self, socket: UdpSocket) -> io::Result<()> {
+        let mut buffer = vec![0u8; DEFAULT_MAX_DATAGRAM_BYTES];
+        loop {
+            let (size, addr) = socket.recv_from(&mut buffer).await?;
+            self.handle_datagram(&buffer[..size], addr);
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Parse and queue one datagram, never waiting for the writer, counting it as dropped or malformed when rejected
+    pub fn handle_datagram(&self, datagram: &[u8], addr: SocketAddr) {
+        let record = match parse_syslog(datagram, &addr.to_string()) {
+            Ok(record) => record,
+            Err(e) => {
+                let detail = format!("syslog datagram from {} - {}", addr, e);
+                report_rejected(&self.config.name, &self.stats.malformed_syslog_messages, 1, "malformed", &detail);
+                return;
+            }
+        };
+        if let Err(e) = self.writer.try_send(record) {
+            let detail = format!("syslog datagram from {} - {}", addr, e);
+            report_rejected(&self.config.name, &self.stats.dropped_syslog_messages, 1, "dropped", &detail);
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Octet-counted or newline-delimited messages, one task per connection
+    async fn run_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (socket, addr) = listener.accept().await?;
+            let name = format!("{}_syslog_{}", self.config.name, addr);
+            let source = addr.to_string();
+            let mut frames = FramedRead::new(socket, SyslogCodec::new(self.config.max_frame_bytes));
+            let writer = self.writer.clone();
+            let stats = self.stats.clone();
+
+            tokio::spawn(async move {
+                println!("{} : client connected", name);
+                while let Some(frame) = frames.next().await {
+                    let frame = match frame {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            eprintln!("{} : syslog framing failed - {}", name, e);
+                            break;
+                        }
+                    };
+                    // A malformed message is skipped, the framing is still in sync
+                    let record = match parse_syslog(&frame, &source) {
+                        Ok(record) => record,
+                        Err(e) => {
+                            report_rejected(&name, &stats.malformed_syslog_messages, 1, "malformed", &format!("syslog message - {}", e));
+                            continue;
+                        }
+                    };
+                    if let Err(e) = writer.send(record).await {
+                        eprintln!("{} : message handling failed - {}", name, e);
+                        break;
+                    }
+                }
+                println!("{} : client disconnected", name);
+            });
+        }
+    }
+}
@@ -1,32 +1,56 @@
 //! TCP socket server for Cap'n Proto messages
+//!
+//! Fire-and-forget by default, or acknowledged when the client opens with a hello (see `ack_protocol`).
 
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
+use std::io;
 use std::sync::Arc;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
+use bytes::BytesMut;
+use capnp::message::ReaderOptions;
+use futures_util::future::{self, BoxFuture};
+use futures_util::stream::FuturesOrdered;
+use futures_util::{FutureExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+use crate::common::auth::{auth_reply, TokenAuth, AUTH_TIMEOUT};
+use crate::common::ack_protocol::{split_message_id, AckFrame, AckHello, AckMode};
 use crate::common::config::ServerConfig;
 use crate::common::safe_socket::SafeSocket;
-use crate::core::writers::LogWriter;
-use crate::core::handlers::handle_tcp_message;
+use crate::common::stats::ServerStats;
+use crate::common::tls;
+use crate::core::writers::{AckStage, WriterHandle};
+use crate::core::handlers::{capnp_reader_options, decode_tcp_message, handle_tcp_message};
+
+
+
 
+/// Acknowledged messages a connection may have in flight before it stops reading
+const MAX_UNACKED_MESSAGES: usize = 1024;
 
+/// Message ID and outcome of an acknowledged message, once it reached the requested stage
+type PendingMessage = BoxFuture<'static, (u64, Result<(), String>)>;
 
+//-----------------------------------------------------------------------------------------------
 
 /// TCP server for Cap'n Proto log messages
 pub struct TcpServer {
     config: ServerConfig,
-    writer: Arc<LogWriter>,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
 }
 
 //-----------------------------------------------------------------------------------------------
 
 impl TcpServer {
     /// Create new TCP server
-    pub fn new(config: &ServerConfig, writer: Arc<LogWriter>) -> Self {
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
         Self {
             config: config.clone(),
             writer,
+            stats,
         }
     }
     
@@ -36,52 +60,153 @@ impl TcpServer {
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         let addr = format!("{}:{}", self.config.host, self.config.port);
         let listener = TcpListener::bind(&addr).await?;
+        let tls_acceptor = match &self.config.tls {
+            Some(tls) => Some(TlsAcceptor::from(tls.rustls_server_config()?)),
+            None => None,
+        };
         
-        println!("{} : TCP server listenning on {}", self.config.name, addr);
-        
-        let sequence_counter = Arc::new(AtomicU64::new(0));
-        let writer_tx = self.writer.start_writer_task();
+        println!(
+            "{} : TCP server listenning on {}{}",
+            self.config.name,
+            addr,
+            if tls_acceptor.is_some() { " (TLS)" } else { "" }
+        );
         
         // Main server loop
         loop {
             let (socket, addr) = listener.accept().await?;
-            let writer_tx = writer_tx.clone();
-            let sequence_counter = sequence_counter.clone();
-            let client_name = format!("{}_client_{}", self.config.name, addr);
+            let max_frame_bytes = self.config.max_frame_bytes;
+            let tls_acceptor = tls_acceptor.clone();
+            let mut connection = Connection::new(
+                &self.config,
+                self.writer.clone(),
+                self.stats.clone(),
+                format!("{}_client_{}", self.config.name, addr),
+                addr.to_string(),
+            );
             
             tokio::spawn(async move {
-                if let Err(e) = Self::handle_tcp_connection(socket, writer_tx, sequence_counter, &client_name).await {
-                    eprintln!("{} : connection handler failed - {}", client_name, e);
+                let result = match tls_acceptor {
+                    Some(tls_acceptor) => match tls::accept(&tls_acceptor, socket).await {
+                        Ok((tls_stream, subject)) => {
+                            // mTLS : the client certificate subject identifies the source
+                            if let Some(subject) = subject {
+                                connection.source = subject;
+                            }
+                            let socket = SafeSocket::new(tls_stream, max_frame_bytes);
+                            Self::handle_tcp_connection(socket, &mut connection).await
+                        }
+                        Err(e) => Err(format!("TLS handshake failed - {}", e).into()),
+                    },
+                    None => {
+                        let socket = SafeSocket::new(socket, max_frame_bytes);
+                        Self::handle_tcp_connection(socket, &mut connection).await
+                    }
+                };
+                if let Err(e) = result {
+                    eprintln!("{} : connection handler failed - {}", connection.name, e);
                 }
             });
         }
     }
     
+    //-----------------------------------------------------------------------------------------------
+
+    /// Serve one client connection over `stream` as if accepted from `source`, e.g. an in-memory
+    /// stream in tests
+    #[cfg(feature = "test-util")]
+    pub async fn serve_stream<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S, source: &str) -> Result<(), String> {
+        let mut connection = Connection::new(
+            &self.config,
+            self.writer.clone(),
+            self.stats.clone(),
+            format!("{}_client_{}", self.config.name, source),
+            source.to_string(),
+        );
+        let socket = SafeSocket::new(stream, self.config.max_frame_bytes);
+        Self::handle_tcp_connection(socket, &mut connection).await.map_err(|e| e.to_string())
+    }
+
     //-----------------------------------------------------------------------------------------------
     
-    /// Handle individual TCP connection
-    async fn handle_tcp_connection(
-        socket: TcpStream,
-        writer_tx: mpsc::Sender<String>,
-        sequence_counter: Arc<AtomicU64>,
-        name: &str,
+    /// Handle individual TCP connection, also serves Unix socket connections
+    pub(crate) async fn handle_tcp_connection<S: AsyncRead + AsyncWrite + Unpin>(
+        mut safe_socket: SafeSocket<S>,
+        connection: &mut Connection,
     ) -> Result<(), Box<dyn std::error::Error>> {
         
-        let mut safe_socket = SafeSocket::new(socket);
+        let name = connection.name.as_str();
         println!("{} : client connected", name);
 
-        loop {
-            let bytes_read = safe_socket.receive_data().await?;
+        // With token authentication the first frame must authenticate the connection
+        if let Some(auth) = &connection.auth {
+            // A client that never authenticates must not hold the connection open
+            let frame = tokio::time::timeout(AUTH_TIMEOUT, Self::receive_frame(&mut safe_socket, connection)).await;
+            let frame = match frame {
+                Ok(frame) => frame?,
+                Err(_) => {
+                    let failures = connection.stats.auth_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                    eprintln!(
+                        "{} : authentication failed - no auth frame within {}s ({} failures in total)",
+                        name,
+                        AUTH_TIMEOUT.as_secs(),
+                        failures
+                    );
+                    let _ = safe_socket.send_data(&auth_reply(false)).await;
+                    return Ok(());
+                }
+            };
+            let Some(data) = frame else {
+                println!("{} : client disconnected", name);
+                return Ok(());
+            };
+            match auth.identify_frame(&data) {
+                Ok(Some(identity)) => {
+                    safe_socket.send_data(&auth_reply(true)).await?;
+                    println!("{} : authenticated as {}", name, identity.0);
+                    connection.client = identity.0;
+                }
+                result => {
+                    let failures = connection.stats.auth_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                    let reason = result.err().unwrap_or_else(|| "unknown token".to_string());
+                    eprintln!("{} : authentication failed - {} ({} failures in total)", name, reason, failures);
+                    let _ = safe_socket.send_data(&auth_reply(false)).await;
+                    return Ok(());
+                }
+            }
+        }
+        let connection = &*connection;
 
-            if bytes_read.is_none() {
+        let mut first_frame = true;
+        loop {
+            let Some(data) = Self::receive_frame(&mut safe_socket, connection).await? else {
                 println!("{} : client disconnected", name);
                 break;
-            }
+            };
 
-            let data = bytes_read.unwrap().to_vec();
+            // A hello as first frame switches the connection to the acknowledged protocol
+            if std::mem::take(&mut first_frame) {
+                match AckHello::parse(&data) {
+                    Some(Ok(hello)) => {
+                        return Self::handle_acknowledged(safe_socket, hello, connection).await;
+                    }
+                    Some(Err(e)) => {
+                        eprintln!("{} : invalid hello - {}", name, e);
+                        break;
+                    }
+                    None => {}
+                }
+            }
             
             // Connection closed, or corrupted message -> close connection, client socket have to manage reconnection
-            if let Err(e) = handle_tcp_message(data, writer_tx.clone(), sequence_counter.clone(), name).await {
+            let result = handle_tcp_message(
+                data.to_vec(),
+                &connection.writer,
+                &connection.source,
+                &connection.client,
+                connection.reader_options,
+            ).await;
+            if let Err(e) = result {
                 eprintln!("{} : message handling failed - {}", name, e);
                 break;
             }
@@ -89,4 +214,157 @@ impl TcpServer {
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Acknowledged protocol : every message is acked or nacked once it reached the requested stage
+    ///
+    /// Messages are pipelined, up to `MAX_UNACKED_MESSAGES` wait for their ack while the connection
+    /// keeps reading. Acks are written in message order, a malformed message is nacked without
+    /// closing the connection.
+    async fn handle_acknowledged<S: AsyncRead + AsyncWrite + Unpin>(
+        mut safe_socket: SafeSocket<S>,
+        hello: AckHello,
+        connection: &Connection,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+
+        let name = connection.name.as_str();
+        safe_socket.send_data(&AckHello::reply()).await?;
+        println!("{} : acknowledged protocol, {:?} acks at stage {:?}", name, hello.mode, hello.stage);
+
+        let mut pending: FuturesOrdered<PendingMessage> = FuturesOrdered::new();
+        let mut receiving = true;
+        while receiving || !pending.is_empty() {
+            tokio::select! {
+                frame = Self::receive_frame(&mut safe_socket, connection), if receiving && pending.len() < MAX_UNACKED_MESSAGES => {
+                    match frame? {
+                        Some(data) => {
+                            let (id, message) = split_message_id(&data)?;
+                            pending.push_back(Self::queue_acknowledged(id, message, hello.stage, connection).await);
+                        }
+                        None => {
+                            println!("{} : client disconnected", name);
+                            receiving = false;
+                        }
+                    }
+                }
+                Some(completed) = pending.next() => {
+                    // Cumulative mode : coalesce every ack already available into one frame
+                    let mut completed = vec![completed];
+                    if hello.mode == AckMode::Cumulative {
+                        while let Some(Some(next)) = pending.next().now_or_never() {
+                            completed.push(next);
+                        }
+                    }
+
+                    let mut last_acked = None;
+                    for (id, result) in completed {
+                        match result {
+                            Ok(()) if hello.mode == AckMode::PerMessage => {
+                                safe_socket.send_data(&AckFrame::Ack(id).encode()).await?;
+                            }
+                            Ok(()) => last_acked = Some(id),
+                            Err(e) => {
+                                // Acks stay in message order : cover the messages before this one first
+                                if let Some(acked) = last_acked.take() {
+                                    safe_socket.send_data(&AckFrame::AckUpTo(acked).encode()).await?;
+                                }
+                                eprintln!("{} : message {} rejected - {}", name, id, e);
+                                safe_socket.send_data(&AckFrame::Nack(id, e).encode()).await?;
+                            }
+                        }
+                    }
+                    if let Some(id) = last_acked {
+                        safe_socket.send_data(&AckFrame::AckUpTo(id).encode()).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Decode and queue one acknowledged message, the returned future resolves with its outcome
+    async fn queue_acknowledged(
+        id: u64,
+        message: &[u8],
+        stage: AckStage,
+        connection: &Connection,
+    ) -> PendingMessage {
+        let record = match decode_tcp_message(message, &connection.source, &connection.client, connection.reader_options) {
+            Ok(record) => record,
+            Err(e) => return Box::pin(future::ready((id, Err(e)))),
+        };
+        match connection.writer.queue(record, stage).await {
+            Ok(pending_ack) => Box::pin(async move { (id, pending_ack.wait().await.map(|_| ())) }),
+            Err(e) => Box::pin(future::ready((id, Err(e)))),
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Receive the next frame, None once the client disconnected or sent an oversized frame
+    async fn receive_frame<S: AsyncRead + AsyncWrite + Unpin>(
+        safe_socket: &mut SafeSocket<S>,
+        connection: &Connection,
+    ) -> io::Result<Option<BytesMut>> {
+        match safe_socket.receive_data().await {
+            Ok(frame) => Ok(frame),
+            // Oversized frame : the stream cannot be resynchronized, drop the client
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                let rejected = connection.stats.oversized_frames.fetch_add(1, Ordering::Relaxed) + 1;
+                eprintln!("{} : frame rejected - {} ({} oversized frames in total)", connection.name, e, rejected);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// State of one client connection, shared by the protocol handlers
+pub(crate) struct Connection {
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+    auth: Option<Arc<TokenAuth>>,
+    reader_options: ReaderOptions,
+    /// Client name used in server messages
+    name: String,
+    /// Record source : remote address, or client certificate subject with mTLS
+    source: String,
+    /// Record client identity, set once the connection authenticated
+    client: String,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl Connection {
+    /// Create the state of a new, not yet authenticated, client connection
+    pub(crate) fn new(
+        config: &ServerConfig,
+        writer: WriterHandle,
+        stats: Arc<ServerStats>,
+        name: String,
+        source: String,
+    ) -> Self {
+        Self {
+            writer,
+            stats,
+            auth: config.auth.clone(),
+            reader_options: capnp_reader_options(config.max_frame_bytes),
+            name,
+            source,
+            client: String::new(),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Client name used in server messages
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+}
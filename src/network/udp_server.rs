@@ -0,0 +1,86 @@
+//! UDP server for Cap'n Proto messages
+//!
+//! Best-effort ingestion : one packed `LoggerMsg` per datagram, no framing, no acknowledgement.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::UdpSocket;
+
+use crate::common::config::{ServerConfig, DEFAULT_MAX_DATAGRAM_BYTES};
+use crate::common::stats::{report_rejected, ServerStats};
+use crate::core::writers::WriterHandle;
+use crate::core::handlers::{capnp_reader_options, decode_tcp_message};
+
+
+
+
+/// UDP server for Cap'n Proto log messages
+pub struct UdpServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl UdpServer {
+    /// Create new UDP server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Run the UDP server
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(port) = self.config.udp_port else {
+            return Err("no UDP port configured".into());
+        };
+        let addr = format!("{}:{}", self.config.host, port);
+        let socket = UdpSocket::bind(&addr).await?;
+        
+        println!("{} : UDP server listening on {}", self.config.name, addr);
+        
+        // One byte more than accepted, so an oversized datagram is detected rather than truncated
+        let mut buffer = vec![0u8; (self.config.max_datagram_bytes + 1).min(DEFAULT_MAX_DATAGRAM_BYTES)];
+        
+        // Main server loop
+        loop {
+            let (size, addr) = socket.recv_from(&mut buffer).await?;
+            self.handle_datagram(&buffer[..size], addr);
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Decode and queue one datagram, counting it as dropped or malformed when rejected
+    pub fn handle_datagram(&self, datagram: &[u8], addr: SocketAddr) {
+        let max_datagram_bytes = self.config.max_datagram_bytes;
+        if datagram.len() > max_datagram_bytes {
+            let detail = format!("datagram over {} bytes from {}", max_datagram_bytes, addr);
+            report_rejected(&self.config.name, &self.stats.dropped_datagrams, 1, "dropped", &detail);
+            return;
+        }
+
+        let reader_options = capnp_reader_options(max_datagram_bytes);
+        let record = match decode_tcp_message(datagram, &addr.to_string(), "", reader_options) {
+            Ok(record) => record,
+            Err(e) => {
+                let detail = format!("datagram from {} - {}", addr, e);
+                report_rejected(&self.config.name, &self.stats.malformed_datagrams, 1, "malformed", &detail);
+                return;
+            }
+        };
+
+        // Never wait for the writer : the kernel would drop datagrams meanwhile, uncounted
+        if let Err(e) = self.writer.try_send(record) {
+            let detail = format!("datagram from {} - {}", addr, e);
+            report_rejected(&self.config.name, &self.stats.dropped_datagrams, 1, "dropped", &detail);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Unix domain socket server for Cap'n Proto messages
+//!
+//! Same framing and protocols as the TCP server, for producers running on the server host.
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::common::config::ServerConfig;
+use crate::common::safe_socket::SafeSocket;
+use crate::common::stats::ServerStats;
+use crate::core::writers::WriterHandle;
+use crate::network::tcp_server::{Connection, TcpServer};
+
+
+
+
+/// Unix domain socket server for Cap'n Proto log messages
+pub struct UnixServer {
+    config: ServerConfig,
+    writer: WriterHandle,
+    stats: Arc<ServerStats>,
+}
+
+//-----------------------------------------------------------------------------------------------
+
+impl UnixServer {
+    /// Create new Unix socket server
+    pub fn new(config: &ServerConfig, writer: WriterHandle, stats: Arc<ServerStats>) -> Self {
+        Self {
+            config: config.clone(),
+            writer,
+            stats,
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Run the Unix socket server
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.config.unix_socket else {
+            return Err("no Unix socket path configured".into());
+        };
+        let listener = Self::bind(path, self.config.unix_socket_mode)?;
+        
+        println!(
+            "{} : Unix socket server listening on {} (mode {:o})",
+            self.config.name,
+            path.display(),
+            self.config.unix_socket_mode
+        );
+        
+        // Main server loop
+        loop {
+            let (socket, _) = listener.accept().await?;
+            let source = Self::peer_source(&socket);
+            let mut connection = Connection::new(
+                &self.config,
+                self.writer.clone(),
+                self.stats.clone(),
+                format!("{}_client_{}", self.config.name, source),
+                source,
+            );
+            let socket = SafeSocket::new(socket, self.config.max_frame_bytes);
+            
+            tokio::spawn(async move {
+                if let Err(e) = TcpServer::handle_tcp_connection(socket, &mut connection).await {
+                    eprintln!("{} : connection handler failed - {}", connection.name(), e);
+                }
+            });
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Bind the socket path and apply its permissions
+    ///
+    /// A socket left by a previous run is replaced, a socket another server still listens on or
+    /// any other file at the path is an error. The socket is bound in a private directory and only
+    /// moved to `path` once its permissions are set, so it is never reachable with wider ones.
+    fn bind(path: &Path, mode: u32) -> io::Result<UnixListener> {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => Self::remove_stale_socket(path)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let file_name = path.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a socket file path", path.display()))
+        })?;
+        let private_dir = path.with_file_name(format!(".{}.bind", std::process::id()));
+        fs::DirBuilder::new().mode(0o700).create(&private_dir)?;
+        let private_path = private_dir.join(file_name);
+
+        let bound = UnixListener::bind(&private_path).and_then(|listener| {
+            fs::set_permissions(&private_path, fs::Permissions::from_mode(mode))?;
+            fs::rename(&private_path, path)?;
+            Ok(listener)
+        });
+        // Also cleans up after a failure : the socket is gone from the directory once renamed
+        let _ = fs::remove_file(&private_path);
+        fs::remove_dir(&private_dir)?;
+        bound
+    }
+
+    //-----------------------------------------------------------------------------------------------
+
+    /// Remove a socket left by a previous run, refuse if a server still answers on it
+    fn remove_stale_socket(path: &Path) -> io::Result<()> {
+        match std::os::unix::net::UnixStream::connect(path) {
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use by another server", path.display()),
+            )),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => fs::remove_file(path),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("cannot check whether {} is in use - {}", path.display(), e),
+            )),
+        }
+    }
+    
+    //-----------------------------------------------------------------------------------------------
+    
+    /// Record source : the peer process credentials, the socket has no remote address
+    fn peer_source(socket: &UnixStream) -> String {
+        match socket.peer_cred() {
+            Ok(cred) => match cred.pid() {
+                Some(pid) => format!("unix:pid={},uid={}", pid, cred.uid()),
+                None => format!("unix:uid={}", cred.uid()),
+            },
+            Err(_) => "unix".to_string(),
+        }
+    }
+}
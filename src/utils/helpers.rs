@@ -1,6 +1,7 @@
 //! Common utility functions
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
 
 
 
@@ -72,4 +73,48 @@ pub fn parse_sequence_number(message: &str) -> Option<(u64, &str)> {
         }
     }
     None
-}
\ No newline at end of file
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Parse a byte size such as `1048576`, `512K`, `100M` or `10G` (binary units)
+pub fn parse_byte_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.char_indices().last()? {
+        (idx, 'K' | 'k') => (&size[..idx], 1u64 << 10),
+        (idx, 'M' | 'm') => (&size[..idx], 1u64 << 20),
+        (idx, 'G' | 'g') => (&size[..idx], 1u64 << 30),
+        (idx, 'T' | 't') => (&size[..idx], 1u64 << 40),
+        _ => (size, 1),
+    };
+    number.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// Non-empty lines of the last `max_bytes` of a file, empty if the file is missing or empty
+///
+/// The first line is dropped when the file is larger, it may be cut in the middle.
+pub async fn read_tail_lines(path: &Path, max_bytes: u64) -> std::io::Result<Vec<String>> {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let file_len = file.metadata().await?.len();
+    let position = file_len.saturating_sub(max_bytes);
+
+    file.seek(SeekFrom::Start(position)).await?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).await?;
+
+    let tail = String::from_utf8_lossy(&tail);
+    let mut lines = tail.lines();
+    if position > 0 {
+        lines.next();
+    }
+    Ok(lines
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
@@ -11,4 +11,6 @@ pub use helpers::{
     get_utc_timestamp,
     validate_file_path,
     parse_sequence_number,
+    parse_byte_size,
+    read_tail_lines,
 };
\ No newline at end of file
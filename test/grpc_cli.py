@@ -29,7 +29,11 @@ def simple_client(client_id):
                 thread_id="ThreadId",
                 thread_name="ThreadName",
                 service_name="ServiceName",
-                stack_trace="StackTrace"
+                stack_trace="StackTrace",
+                attributes=[
+                    log_service_pb2.Attribute(key="order_id", string_value="A-17"),
+                    log_service_pb2.Attribute(key="latency_ms", float_value=12.5),
+                ]
             )
             
             
@@ -24,19 +24,27 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x11log_service.proto\x12\nlogservice\"\xd7\x02\n\nLogRequest\x12\x11\n\ttimestamp\x18\x01 \x01(\t\x12\x10\n\x08hostname\x18\x02 \x01(\t\x12\x13\n\x0blogger_name\x18\x03 \x01(\t\x12\x0e\n\x06module\x18\x04 \x01(\t\x12 \n\x05level\x18\x05 \x01(\x0e\x32\x11.logservice.Level\x12\x10\n\x08\x66ilename\x18\x06 \x01(\t\x12\x15\n\rfunction_name\x18\x07 \x01(\t\x12\x13\n\x0bline_number\x18\x08 \x01(\t\x12\x0f\n\x07message\x18\t \x01(\t\x12\x11\n\tpath_name\x18\n \x01(\t\x12\x12\n\nprocess_id\x18\x0b \x01(\t\x12\x14\n\x0cprocess_name\x18\x0c \x01(\t\x12\x11\n\tthread_id\x18\r \x01(\t\x12\x13\n\x0bthread_name\x18\x0e \x01(\t\x12\x14\n\x0cservice_name\x18\x0f \x01(\t\x12\x13\n\x0bstack_trace\x18\x10 \x01(\t\"\x1e\n\x0bLogResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08*\x96\x01\n\x05Level\x12\n\n\x06NOTSET\x10\x00\x12\t\n\x05\x44\x45\x42UG\x10\x01\x12\n\n\x06STREAM\x10\x02\x12\x08\n\x04INFO\x10\x03\x12\t\n\x05LOGON\x10\x04\x12\n\n\x06LOGOUT\x10\x05\x12\t\n\x05TRADE\x10\x06\x12\x0c\n\x08SCHEDULE\x10\x07\x12\n\n\x06REPORT\x10\x08\x12\x0b\n\x07WARNING\x10\t\x12\t\n\x05\x45RROR\x10\n\x12\x0c\n\x08\x43RITICAL\x10\x0b\x32K\n\nLogService\x12=\n\nLogMessage\x12\x16.logservice.LogRequest\x1a\x17.logservice.LogResponseb\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x11log_service.proto\x12\nlogservice\"\x82\x03\n\nLogRequest\x12\x11\n\ttimestamp\x18\x01 \x01(\t\x12\x10\n\x08hostname\x18\x02 \x01(\t\x12\x13\n\x0blogger_name\x18\x03 \x01(\t\x12\x0e\n\x06module\x18\x04 \x01(\t\x12 \n\x05level\x18\x05 \x01(\x0e\x32\x11.logservice.Level\x12\x10\n\x08\x66ilename\x18\x06 \x01(\t\x12\x15\n\rfunction_name\x18\x07 \x01(\t\x12\x13\n\x0bline_number\x18\x08 \x01(\t\x12\x0f\n\x07message\x18\t \x01(\t\x12\x11\n\tpath_name\x18\n \x01(\t\x12\x12\n\nprocess_id\x18\x0b \x01(\t\x12\x14\n\x0cprocess_name\x18\x0c \x01(\t\x12\x11\n\tthread_id\x18\r \x01(\t\x12\x13\n\x0bthread_name\x18\x0e \x01(\t\x12\x14\n\x0cservice_name\x18\x0f \x01(\t\x12\x13\n\x0bstack_trace\x18\x10 \x01(\t\x12)\n\nattributes\x18\x11 \x03(\x0b\x32\x15.logservice.Attribute\"{\n\tAttribute\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\x16\n\x0cstring_value\x18\x02 \x01(\tH\x00\x12\x13\n\tint_value\x18\x03 \x01(\x03H\x00\x12\x15\n\x0b\x66loat_value\x18\x04 \x01(\x01H\x00\x12\x14\n\nbool_value\x18\x05 \x01(\x08H\x00\x42\x07\n\x05value\"\x1e\n\x0bLogResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\";\n\x0fLogBatchRequest\x12(\n\x08requests\x18\x01 \x03(\x0b\x32\x16.logservice.LogRequest\"m\n\x10LogBatchResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x10\n\x08\x61\x63\x63\x65pted\x18\x02 \x01(\x04\x12\x10\n\x08rejected\x18\x03 \x01(\x04\x12$\n\x06\x65rrors\x18\x04 \x03(\x0b\x32\x14.logservice.LogError\"(\n\x08LogError\x12\r\n\x05index\x18\x01 \x01(\x04\x12\r\n\x05\x65rror\x18\x02 \x01(\t*\x96\x01\n\x05Level\x12\n\n\x06NOTSET\x10\x00\x12\t\n\x05\x44\x45\x42UG\x10\x01\x12\n\n\x06STREAM\x10\x02\x12\x08\n\x04INFO\x10\x03\x12\t\n\x05LOGON\x10\x04\x12\n\n\x06LOGOUT\x10\x05\x12\t\n\x05TRADE\x10\x06\x12\x0c\n\x08SCHEDULE\x10\x07\x12\n\n\x06REPORT\x10\x08\x12\x0b\n\x07WARNING\x10\t\x12\t\n\x05\x45RROR\x10\n\x12\x0c\n\x08\x43RITICAL\x10\x0b\x32\xd8\x01\n\nLogService\x12=\n\nLogMessage\x12\x16.logservice.LogRequest\x1a\x17.logservice.LogResponse\x12\x45\n\x08LogBatch\x12\x1b.logservice.LogBatchRequest\x1a\x1c.logservice.LogBatchResponse\x12\x44\n\nStreamLogs\x12\x16.logservice.LogRequest\x1a\x1c.logservice.LogBatchResponse(\x01\x62\x06proto3')
 
 _globals = globals()
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, _globals)
 _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'log_service_pb2', _globals)
 if not _descriptor._USE_C_DESCRIPTORS:
   DESCRIPTOR._loaded_options = None
-  _globals['_LEVEL']._serialized_start=412
-  _globals['_LEVEL']._serialized_end=562
+  _globals['_LEVEL']._serialized_start=794
+  _globals['_LEVEL']._serialized_end=944
   _globals['_LOGREQUEST']._serialized_start=34
-  _globals['_LOGREQUEST']._serialized_end=377
-  _globals['_LOGRESPONSE']._serialized_start=379
-  _globals['_LOGRESPONSE']._serialized_end=409
-  _globals['_LOGSERVICE']._serialized_start=564
-  _globals['_LOGSERVICE']._serialized_end=639
+  _globals['_LOGREQUEST']._serialized_end=420
+  _globals['_ATTRIBUTE']._serialized_start=422
+  _globals['_ATTRIBUTE']._serialized_end=545
+  _globals['_LOGRESPONSE']._serialized_start=547
+  _globals['_LOGRESPONSE']._serialized_end=577
+  _globals['_LOGBATCHREQUEST']._serialized_start=579
+  _globals['_LOGBATCHREQUEST']._serialized_end=638
+  _globals['_LOGBATCHRESPONSE']._serialized_start=640
+  _globals['_LOGBATCHRESPONSE']._serialized_end=749
+  _globals['_LOGERROR']._serialized_start=751
+  _globals['_LOGERROR']._serialized_end=791
+  _globals['_LOGSERVICE']._serialized_start=947
+  _globals['_LOGSERVICE']._serialized_end=1163
 # @@protoc_insertion_point(module_scope)
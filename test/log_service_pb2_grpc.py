@@ -39,6 +39,16 @@ class LogServiceStub(object):
                 request_serializer=log__service__pb2.LogRequest.SerializeToString,
                 response_deserializer=log__service__pb2.LogResponse.FromString,
                 _registered_method=True)
+        self.LogBatch = channel.unary_unary(
+                '/logservice.LogService/LogBatch',
+                request_serializer=log__service__pb2.LogBatchRequest.SerializeToString,
+                response_deserializer=log__service__pb2.LogBatchResponse.FromString,
+                _registered_method=True)
+        self.StreamLogs = channel.stream_unary(
+                '/logservice.LogService/StreamLogs',
+                request_serializer=log__service__pb2.LogRequest.SerializeToString,
+                response_deserializer=log__service__pb2.LogBatchResponse.FromString,
+                _registered_method=True)
 
 
 class LogServiceServicer(object):
@@ -50,6 +60,20 @@ class LogServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def LogBatch(self, request, context):
+        """Several messages in one round trip, written in request order
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def StreamLogs(self, request_iterator, context):
+        """Client-streaming : messages are queued as they arrive, one summary once the stream ends
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_LogServiceServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -58,6 +82,16 @@ def add_LogServiceServicer_to_server(servicer, server):
                     request_deserializer=log__service__pb2.LogRequest.FromString,
                     response_serializer=log__service__pb2.LogResponse.SerializeToString,
             ),
+            'LogBatch': grpc.unary_unary_rpc_method_handler(
+                    servicer.LogBatch,
+                    request_deserializer=log__service__pb2.LogBatchRequest.FromString,
+                    response_serializer=log__service__pb2.LogBatchResponse.SerializeToString,
+            ),
+            'StreamLogs': grpc.stream_unary_rpc_method_handler(
+                    servicer.StreamLogs,
+                    request_deserializer=log__service__pb2.LogRequest.FromString,
+                    response_serializer=log__service__pb2.LogBatchResponse.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'logservice.LogService', rpc_method_handlers)
@@ -95,3 +129,57 @@ class LogService(object):
             timeout,
             metadata,
             _registered_method=True)
+
+    @staticmethod
+    def LogBatch(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/logservice.LogService/LogBatch',
+            log__service__pb2.LogBatchRequest.SerializeToString,
+            log__service__pb2.LogBatchResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def StreamLogs(request_iterator,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.stream_unary(
+            request_iterator,
+            target,
+            '/logservice.LogService/StreamLogs',
+            log__service__pb2.LogRequest.SerializeToString,
+            log__service__pb2.LogBatchResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
@@ -56,6 +56,11 @@ def send_capnp_message():
     logger_msg.threadName         = "ThreadName" #string // Thread name
     logger_msg.serviceName        = "ServiceName" #string // Name of the service generating the log
     logger_msg.stackTrace         = "StackTrace" #string // Stack trace if available
+    attributes                    = logger_msg.init("attributes", 2) # typed key / value context
+    attributes[0].key             = "order_id"
+    attributes[0].value.stringValue = "A-17"
+    attributes[1].key             = "latency_ms"
+    attributes[1].value.floatValue = 12.5
 
     # Serialize the message
     return logger_msg.to_bytes_packed()
@@ -0,0 +1,193 @@
+//! Acknowledged TCP protocol tests : hello parsing, per-message and cumulative acks, nacks
+
+use std::sync::Arc;
+
+use capnp::{message, serialize_packed};
+use log_server::common::ack_protocol::{AckFrame, AckHello, AckMode};
+use log_server::common::config::ServerConfig;
+use log_server::common::safe_socket::SafeSocket;
+use log_server::common::stats::ServerStats;
+use log_server::core::writers::{AckStage, QueuedRecords, WriterHandle};
+use log_server::logger_capnp::logger_msg::{logger_msg, Level};
+use log_server::network::tcp_server::TcpServer;
+use tokio::io::DuplexStream;
+use tokio::task::JoinHandle;
+
+
+
+
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a packed `LoggerMsg`
+fn packed_message(message: &str) -> Vec<u8> {
+    let mut builder = message::Builder::new_default();
+    let mut log_message = builder.init_root::<logger_msg::Builder<'_>>();
+    log_message.set_message(message);
+    log_message.set_level(Level::Info);
+    let mut data = Vec::new();
+    serialize_packed::write_message(&mut data, &builder).unwrap();
+    data
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build an acknowledged frame : message ID then payload
+fn frame(id: u64, payload: &[u8]) -> Vec<u8> {
+    let mut frame = id.to_be_bytes().to_vec();
+    frame.extend_from_slice(payload);
+    frame
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a hello frame
+fn hello(mode: u8, stage: u8) -> Vec<u8> {
+    let mut hello = b"LSAK".to_vec();
+    hello.extend_from_slice(&[1, mode, stage]);
+    hello
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to connect an in-memory client to a TCP server over a detached writer queue
+//
+// Records are acknowledged once taken from the returned queue.
+fn connect() -> (SafeSocket<DuplexStream>, QueuedRecords, JoinHandle<Result<(), String>>) {
+    let config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    let (writer, records) = WriterHandle::detached(64);
+    let server = TcpServer::new(&config, writer, Arc::new(ServerStats::default()));
+    let (client, server_side) = tokio::io::duplex(MAX_FRAME_BYTES);
+    let served = tokio::spawn(async move { server.serve_stream(server_side, "10.0.0.8:40000").await });
+    (SafeSocket::new(client, MAX_FRAME_BYTES), records, served)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to open the acknowledged protocol
+async fn open(client: &mut SafeSocket<DuplexStream>, mode: u8, stage: u8) {
+    client.send_data(&hello(mode, stage)).await.unwrap();
+    let reply = client.receive_data().await.unwrap().unwrap();
+    assert_eq!(reply.as_ref(), AckHello::reply().as_slice());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to read the next ack frame
+async fn next_ack(client: &mut SafeSocket<DuplexStream>) -> AckFrame {
+    AckFrame::decode(&client.receive_data().await.unwrap().unwrap()).unwrap()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to take `count` queued records, acknowledging them, and return their messages
+async fn take(records: &mut QueuedRecords, count: usize) -> Vec<String> {
+    let mut messages = Vec::new();
+    for _ in 0..count {
+        messages.push(records.recv().await.unwrap().message);
+    }
+    messages
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_hellos() {
+    assert_eq!(
+        AckHello::parse(&hello(0, 1)),
+        Some(Ok(AckHello {
+            mode: AckMode::PerMessage,
+            stage: AckStage::Written
+        }))
+    );
+    assert_eq!(
+        AckHello::parse(&hello(1, 2)),
+        Some(Ok(AckHello {
+            mode: AckMode::Cumulative,
+            stage: AckStage::Durable
+        }))
+    );
+
+    // Not a hello : a legacy message
+    assert_eq!(AckHello::parse(&packed_message("legacy")), None);
+
+    let error = |payload: &[u8]| AckHello::parse(payload).unwrap().unwrap_err();
+    assert_eq!(error(b"LSAK\x01\x00"), "hello of 6 bytes, expected 7");
+    assert_eq!(error(b"LSAK\x02\x00\x00"), "unsupported protocol version 2");
+    assert_eq!(error(b"LSAK\x01\x05\x00"), "invalid ack mode 5");
+    assert_eq!(error(b"LSAK\x01\x00\x03"), "invalid ack stage 3");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn acks_each_message_and_nacks_without_closing() {
+    let (mut client, mut records, served) = connect();
+    open(&mut client, 0, 1).await;
+
+    client.send_data(&frame(1, &packed_message("first"))).await.unwrap();
+    client.send_data(&frame(2, b"\xff\xff not capnp")).await.unwrap();
+    client.send_data(&frame(3, &packed_message("third"))).await.unwrap();
+    assert_eq!(take(&mut records, 2).await, ["first", "third"]);
+
+    assert_eq!(next_ack(&mut client).await, AckFrame::Ack(1));
+    assert!(matches!(next_ack(&mut client).await, AckFrame::Nack(2, _)));
+    assert_eq!(next_ack(&mut client).await, AckFrame::Ack(3));
+
+    // The connection survived the malformed message
+    client.send_data(&frame(4, &packed_message("fourth"))).await.unwrap();
+    assert_eq!(take(&mut records, 1).await, ["fourth"]);
+    assert_eq!(next_ack(&mut client).await, AckFrame::Ack(4));
+
+    drop(client);
+    assert_eq!(served.await.unwrap(), Ok(()));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn acks_cumulatively_in_message_order() {
+    let (mut client, mut records, _served) = connect();
+    open(&mut client, 1, 0).await;
+
+    for id in 1..=3 {
+        client.send_data(&frame(id, &packed_message(&format!("message-{}", id)))).await.unwrap();
+    }
+    take(&mut records, 3).await;
+
+    // Acks may be coalesced, the last one covers every message
+    let mut last = 0;
+    while last < 3 {
+        let AckFrame::AckUpTo(id) = next_ack(&mut client).await else {
+            panic!("expected a cumulative ack");
+        };
+        assert!(id > last);
+        last = id;
+    }
+
+    // A nack splits the cumulative acks so they stay in message order
+    client.send_data(&frame(4, &packed_message("message-4"))).await.unwrap();
+    client.send_data(&frame(5, b"")).await.unwrap();
+    client.send_data(&frame(6, &packed_message("message-6"))).await.unwrap();
+    assert_eq!(take(&mut records, 2).await, ["message-4", "message-6"]);
+
+    assert_eq!(next_ack(&mut client).await, AckFrame::AckUpTo(4));
+    assert!(matches!(next_ack(&mut client).await, AckFrame::Nack(5, _)));
+    assert_eq!(next_ack(&mut client).await, AckFrame::AckUpTo(6));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn closes_the_connection_on_a_malformed_hello() {
+    for malformed in [b"LSAK\x01\x00".to_vec(), hello(0, 9)] {
+        let (mut client, mut records, served) = connect();
+        client.send_data(&malformed).await.unwrap();
+
+        // No reply, the connection is closed and nothing is queued
+        assert!(client.receive_data().await.unwrap().is_none());
+        assert_eq!(served.await.unwrap(), Ok(()));
+        assert!(records.try_recv().is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Attribute tests : typed key / values carried by Cap'n Proto and JSON log messages, and their output
+
+use capnp::{message, serialize_packed};
+use log_server::core::formatters::{format_json_line, TextTemplate};
+use log_server::core::handlers::{capnp_reader_options, decode_json_message, decode_tcp_message};
+use log_server::core::records::{AttributeValue, LogField, LogRecord};
+use log_server::logger_capnp::logger_msg::{logger_msg, Level};
+use log_server::network::grpc_server::log_service::attribute::Value as ProtoAttributeValue;
+use serde_json::{json, Value};
+
+
+
+
+const SOURCE: &str = "10.0.0.7:9020";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a packed `LoggerMsg`, `attributes` left unset when None as older clients do
+fn capnp_message(attributes: Option<&[(&str, AttributeValue)]>) -> Vec<u8> {
+    let mut builder = message::Builder::new_default();
+    let mut log_message = builder.init_root::<logger_msg::Builder<'_>>();
+    log_message.set_message("order filled");
+    log_message.set_level(Level::Trade);
+    if let Some(attributes) = attributes {
+        let mut list = log_message.init_attributes(attributes.len() as u32);
+        for (index, (key, value)) in attributes.iter().enumerate() {
+            let mut attribute = list.reborrow().get(index as u32);
+            attribute.set_key(*key);
+            let mut slot = attribute.init_value();
+            match value {
+                AttributeValue::String(text) => slot.set_string_value(text.as_str()),
+                AttributeValue::Int(value) => slot.set_int_value(*value),
+                AttributeValue::Float(value) => slot.set_float_value(*value),
+                AttributeValue::Bool(value) => slot.set_bool_value(*value),
+            }
+        }
+    }
+    let mut data = Vec::new();
+    serialize_packed::write_message(&mut data, &builder).unwrap();
+    data
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_typed_capnp_attributes_in_order() {
+    let attributes = [
+        ("order_id", AttributeValue::String("A-17".to_string())),
+        ("qty", AttributeValue::Int(-150)),
+        ("latency_ms", AttributeValue::Float(12.5)),
+        ("replay", AttributeValue::Bool(true)),
+    ];
+    let data = capnp_message(Some(&attributes));
+
+    let record = decode_tcp_message(&data, SOURCE, "", capnp_reader_options(1024)).unwrap();
+    assert_eq!(record.message, "order filled");
+    assert_eq!(record.level_name(), "TRADE");
+    let expected: Vec<(String, AttributeValue)> = attributes
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+    assert_eq!(record.attributes, expected);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_capnp_messages_without_attributes_and_rejects_empty_keys() {
+    let record = decode_tcp_message(&capnp_message(None), SOURCE, "", capnp_reader_options(1024)).unwrap();
+    assert!(record.attributes.is_empty());
+
+    let data = capnp_message(Some(&[("", AttributeValue::Int(1))]));
+    assert!(decode_tcp_message(&data, SOURCE, "", capnp_reader_options(1024)).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_json_attributes_object() {
+    let item = json!({
+        "message": "order filled",
+        "attributes": {"order_id": "A-17", "qty": 150, "latency_ms": 12.5, "replay": false},
+    });
+
+    let log_request = decode_json_message(&item).unwrap();
+    let attributes: Vec<(&str, Option<ProtoAttributeValue>)> = log_request
+        .attributes
+        .iter()
+        .map(|attribute| (attribute.key.as_str(), attribute.value.clone()))
+        .collect();
+    assert_eq!(
+        attributes,
+        vec![
+            ("order_id", Some(ProtoAttributeValue::StringValue("A-17".to_string()))),
+            ("qty", Some(ProtoAttributeValue::IntValue(150))),
+            ("latency_ms", Some(ProtoAttributeValue::FloatValue(12.5))),
+            ("replay", Some(ProtoAttributeValue::BoolValue(false))),
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_invalid_json_attributes() {
+    assert!(decode_json_message(&json!({"attributes": ["order_id", "A-17"]})).is_err());
+    assert!(decode_json_message(&json!({"attributes": {"order": {"id": "A-17"}}})).is_err());
+    assert!(decode_json_message(&json!({"attributes": {"order_id": null}})).is_err());
+    assert!(decode_json_message(&json!({"attributes": null})).unwrap().attributes.is_empty());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a record carrying a thread name and typed attributes
+fn attributed_record() -> LogRecord {
+    let mut record = LogRecord::new(SOURCE);
+    record.sequence = 7;
+    record.message = "order filled".to_string();
+    record.thread_name = "pool 1".to_string();
+    record.attributes = vec![
+        ("order_id".to_string(), AttributeValue::String("A-17".to_string())),
+        ("qty".to_string(), AttributeValue::Int(-150)),
+        ("latency_ms".to_string(), AttributeValue::Float(12.5)),
+        ("replay".to_string(), AttributeValue::Bool(true)),
+        ("note".to_string(), AttributeValue::String("partial \"fill\"".to_string())),
+        ("ratio".to_string(), AttributeValue::Float(f64::NAN)),
+    ];
+    record
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn renders_attributes_as_key_values_after_the_extra_fields() {
+    let template = TextTemplate::parse("{seq} {message}{extras}").unwrap();
+    let record = attributed_record();
+
+    assert_eq!(
+        template.render(&record, &[LogField::Module, LogField::ThreadName]),
+        "7 order filled | thread_name=\"pool 1\" order_id=A-17 qty=-150 latency_ms=12.5 replay=true \
+         note=\"partial \\\"fill\\\"\" ratio=NaN"
+    );
+
+    // Without extra fields the attributes open the ` | ` section, without either nothing is added
+    let mut record = attributed_record();
+    record.attributes.truncate(1);
+    assert_eq!(template.render(&record, &[]), "7 order filled | order_id=A-17");
+    record.attributes.clear();
+    assert_eq!(template.render(&record, &[]), "7 order filled");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn writes_typed_attributes_object_in_json_lines() {
+    let line: Value = serde_json::from_str(&format_json_line(&attributed_record())).unwrap();
+
+    assert_eq!(line["seq"], 7);
+    assert_eq!(line["message"], "order filled");
+    assert_eq!(
+        line["attributes"],
+        json!({
+            "order_id": "A-17",
+            "qty": -150,
+            "latency_ms": 12.5,
+            "replay": true,
+            "note": "partial \"fill\"",
+            "ratio": "NaN",
+        })
+    );
+    let keys: Vec<&String> = line["attributes"].as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["order_id", "qty", "latency_ms", "replay", "note", "ratio"]);
+
+    // No attributes, no `attributes` key
+    let mut record = attributed_record();
+    record.attributes.clear();
+    let line: Value = serde_json::from_str(&format_json_line(&record)).unwrap();
+    assert!(line.get("attributes").is_none());
+}
@@ -0,0 +1,155 @@
+//! Authentication tests : token file parsing, auth frames and the TCP handshake
+
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use capnp::{message, serialize_packed};
+use log_server::common::auth::{auth_reply, ClientIdentity, TokenAuth, AUTH_TIMEOUT};
+use log_server::common::config::ServerConfig;
+use log_server::common::safe_socket::SafeSocket;
+use log_server::common::stats::ServerStats;
+use log_server::core::writers::{QueuedRecords, WriterHandle};
+use log_server::logger_capnp::logger_msg::{logger_msg, Level};
+use log_server::network::tcp_server::TcpServer;
+use tokio::io::DuplexStream;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+
+
+
+const TOKENS: &str = "# identity  token\ntrader-01   5f0c1e9a7d2b4c8e\nrisk-engine 9b3d7a61e04f2c58  # risk\n";
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a packed `LoggerMsg`
+fn packed_message(message: &str) -> Vec<u8> {
+    let mut builder = message::Builder::new_default();
+    let mut log_message = builder.init_root::<logger_msg::Builder<'_>>();
+    log_message.set_message(message);
+    log_message.set_level(Level::Info);
+    let mut data = Vec::new();
+    serialize_packed::write_message(&mut data, &builder).unwrap();
+    data
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build an auth frame
+fn auth_frame(token: &str) -> Vec<u8> {
+    let mut frame = b"LSAU".to_vec();
+    frame.extend_from_slice(token.as_bytes());
+    frame
+}
+
+//-----------------------------------------------------------------------------------------------
+
+/// In-memory client, records its server queued, server stats and the served connection
+type TestConnection = (SafeSocket<DuplexStream>, QueuedRecords, Arc<ServerStats>, JoinHandle<Result<(), String>>);
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to connect an in-memory client to a TCP server requiring the test tokens
+fn connect() -> TestConnection {
+    let mut config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    config.auth = Some(Arc::new(TokenAuth::parse(TOKENS).unwrap()));
+    let (writer, records) = WriterHandle::detached(8);
+    let stats = Arc::new(ServerStats::default());
+    let server = TcpServer::new(&config, writer, stats.clone());
+    let (client, server_side) = tokio::io::duplex(MAX_FRAME_BYTES);
+    let served = tokio::spawn(async move { server.serve_stream(server_side, "10.0.0.9:40000").await });
+    (SafeSocket::new(client, MAX_FRAME_BYTES), records, stats, served)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper checking the server rejected the client : rejection reply, then the connection closes
+async fn assert_rejected(client: &mut SafeSocket<DuplexStream>, stats: &ServerStats) {
+    let reply = client.receive_data().await.unwrap().unwrap();
+    assert_eq!(reply.as_ref(), auth_reply(false).as_slice());
+    assert!(client.receive_data().await.unwrap().is_none());
+    assert_eq!(stats.auth_failures.load(Ordering::Relaxed), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_token_files() {
+    let auth = TokenAuth::parse(TOKENS).unwrap();
+    assert_eq!(auth.len(), 2);
+    assert_eq!(auth.identify("9b3d7a61e04f2c58"), Some(ClientIdentity("risk-engine".to_string())));
+    assert_eq!(auth.identify("9b3d7a61e04f2c5"), None);
+
+    assert_eq!(TokenAuth::parse("trader-01\n").unwrap_err(), "line 1 : expected '<identity> <token>'");
+    assert_eq!(TokenAuth::parse("a t b\n").unwrap_err(), "line 1 : expected '<identity> <token>'");
+    assert_eq!(TokenAuth::parse("a token\nb token\n").unwrap_err(), "line 2 : duplicate token");
+    assert_eq!(TokenAuth::parse("# nothing\n\n").unwrap_err(), "no token defined");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn identifies_auth_frames() {
+    let auth = TokenAuth::parse(TOKENS).unwrap();
+
+    assert_eq!(
+        auth.identify_frame(&auth_frame("5f0c1e9a7d2b4c8e")),
+        Ok(Some(ClientIdentity("trader-01".to_string())))
+    );
+    assert_eq!(auth.identify_frame(&auth_frame("wrong")), Ok(None));
+    assert!(auth.identify_frame(&packed_message("no auth")).is_err());
+    assert!(auth.identify_frame(b"LSAU\xff\xfe").is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn accepts_a_good_token_and_stamps_the_identity() {
+    let (mut client, mut records, stats, _served) = connect();
+
+    client.send_data(&auth_frame("5f0c1e9a7d2b4c8e")).await.unwrap();
+    let reply = client.receive_data().await.unwrap().unwrap();
+    assert_eq!(reply.as_ref(), auth_reply(true).as_slice());
+
+    client.send_data(&packed_message("order settled")).await.unwrap();
+    let record = records.recv().await.unwrap();
+    assert_eq!(record.message, "order settled");
+    assert_eq!(record.client, "trader-01");
+    assert_eq!(stats.auth_failures.load(Ordering::Relaxed), 0);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rejects_a_bad_token() {
+    let (mut client, mut records, stats, served) = connect();
+
+    client.send_data(&auth_frame("wrong")).await.unwrap();
+    assert_rejected(&mut client, &stats).await;
+    assert_eq!(served.await.unwrap(), Ok(()));
+    assert!(records.try_recv().is_none());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rejects_a_message_sent_without_auth_frame() {
+    let (mut client, mut records, stats, _served) = connect();
+
+    client.send_data(&packed_message("not authenticated")).await.unwrap();
+    assert_rejected(&mut client, &stats).await;
+    assert!(records.try_recv().is_none());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test(start_paused = true)]
+async fn rejects_a_client_that_never_authenticates() {
+    let (mut client, _records, stats, served) = connect();
+    let started = Instant::now();
+
+    assert_rejected(&mut client, &stats).await;
+    assert_eq!(started.elapsed(), AUTH_TIMEOUT);
+    assert_eq!(served.await.unwrap(), Ok(()));
+}
@@ -0,0 +1,136 @@
+//! Durability tests : mode parsing and the points where the writer flushes and fsyncs
+
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use log_server::core::records::LogRecord;
+use log_server::core::writers::{AckStage, DurabilityMode, LogWriter, WriterHandle};
+use log_server::WriterConfig;
+
+
+
+
+const SOURCE: &str = "10.0.0.6:9020";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to start a writer on `dir/_main.log` with the given durability and size limit
+async fn start_writer(dir: &Path, durability: DurabilityMode, max_file_bytes: u64) -> WriterHandle {
+    let config = WriterConfig {
+        log_path: dir.join("_main.log"),
+        durability,
+        max_file_bytes,
+        ..WriterConfig::default()
+    };
+    LogWriter::new(config).await.unwrap().start_writer_task()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to write one record and wait until it is in the log file
+async fn write_one(handle: &WriterHandle) {
+    handle.send_and_wait(LogRecord::new(SOURCE), AckStage::Written).await.unwrap();
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper reading the fsync counter
+fn syncs(handle: &WriterHandle) -> u64 {
+    handle.stats().syncs.load(Ordering::Relaxed)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_durability_modes() {
+    assert_eq!(DurabilityMode::from_name("none"), Some(DurabilityMode::NoSync));
+    assert_eq!(DurabilityMode::from_name("batch"), Some(DurabilityMode::Batch));
+    assert_eq!(DurabilityMode::from_name("ack"), Some(DurabilityMode::Ack));
+    assert_eq!(
+        DurabilityMode::from_name("200ms"),
+        Some(DurabilityMode::Interval(Duration::from_millis(200)))
+    );
+
+    for invalid in ["0ms", "ms", "200", "-5ms", "always", ""] {
+        assert_eq!(DurabilityMode::from_name(invalid), None, "{}", invalid);
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn none_flushes_without_fsync() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path(), DurabilityMode::NoSync, 0).await;
+
+    write_one(&handle).await;
+    write_one(&handle).await;
+
+    // Written means flushed to the file, readable by another process
+    let content = std::fs::read_to_string(dir.path().join("_main.log")).unwrap();
+    assert_eq!(content.lines().count(), 2);
+    assert_eq!(syncs(&handle), 0);
+
+    // A durable acknowledgement still forces an fsync
+    handle.send_and_wait(LogRecord::new(SOURCE), AckStage::Durable).await.unwrap();
+    assert_eq!(syncs(&handle), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn batch_and_ack_fsync_every_batch_before_acknowledging() {
+    for durability in [DurabilityMode::Batch, DurabilityMode::Ack] {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = start_writer(dir.path(), durability, 0).await;
+
+        write_one(&handle).await;
+        assert_eq!(syncs(&handle), 1, "{:?}", durability);
+        write_one(&handle).await;
+        assert_eq!(syncs(&handle), 2, "{:?}", durability);
+    }
+
+    // In ack mode, a plain acknowledged send already waits for the fsync
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path(), DurabilityMode::Ack, 0).await;
+    handle.send_acknowledged(LogRecord::new(SOURCE)).await.unwrap();
+    assert_eq!(syncs(&handle), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn interval_fsyncs_once_after_the_interval() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path(), DurabilityMode::Interval(Duration::from_millis(50)), 0).await;
+
+    write_one(&handle).await;
+    write_one(&handle).await;
+    assert_eq!(syncs(&handle), 0);
+
+    for _ in 0..100 {
+        if syncs(&handle) > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    // One fsync covers both records, none follows without new data
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(syncs(&handle), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rotation_fsyncs_unless_the_mode_is_none() {
+    for (durability, synced) in [(DurabilityMode::Interval(Duration::from_secs(3600)), true), (DurabilityMode::NoSync, false)] {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = start_writer(dir.path(), durability, 1).await;
+
+        // The first record fills the file, it rotates before the second one is written
+        write_one(&handle).await;
+        write_one(&handle).await;
+        assert_eq!(syncs(&handle) > 0, synced, "{:?}", durability);
+    }
+}
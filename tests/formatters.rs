@@ -0,0 +1,132 @@
+//! Formatter tests : default text line, text templates and field lists
+
+use log_server::core::formatters::{format_record, OutputFormat, TextTemplate, DEFAULT_TEXT_TEMPLATE};
+use log_server::core::records::{LogField, LogRecord};
+use log_server::logger_capnp::logger_msg::Level;
+
+
+
+
+const SOURCE: &str = "10.0.0.3:9020";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a record filling the columns of the default line, some longer than their column
+fn column_record() -> LogRecord {
+    let mut record = LogRecord::new(SOURCE);
+    record.sequence = 42;
+    record.timestamp = "2025-01-15T10:30:45.123456+00:00".to_string();
+    record.hostname = "trading-host-eu-west-1".to_string();
+    record.logger_name = "com.bank.settlement.Engine".to_string();
+    record.level = Level::Warning;
+    record.filename = "settlement_engine_core.py".to_string();
+    record.function_name = "reconcile_pending_settlements".to_string();
+    record.line_number = "1234567".to_string();
+    record.message = "settlement delayed".to_string();
+    record
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to truncate like the historical formatter
+fn truncate(text: &str, max_len: usize) -> &str {
+    &text[..text.len().min(max_len)]
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn default_text_line_keeps_the_historical_layout() {
+    let record = column_record();
+    let expected = format!(
+        "{} {:<33} {:<12} {:<15} {:<8} {:<20} {:<25} {:<6} {}",
+        record.sequence,
+        record.timestamp,
+        truncate(&record.hostname, 12),
+        truncate(&record.logger_name, 15),
+        truncate(record.level_name(), 8),
+        truncate(&record.filename, 20),
+        truncate(&record.function_name, 25),
+        truncate(&record.line_number, 6),
+        record.message
+    );
+
+    let line = format_record(&record, OutputFormat::Text, &TextTemplate::default(), &[]);
+    assert_eq!(line, expected);
+
+    // Short values are padded to their column
+    let mut record = LogRecord::new(SOURCE);
+    record.level = Level::Info;
+    record.message = "up".to_string();
+    let line = format_record(&record, OutputFormat::Text, &TextTemplate::default(), &[]);
+    assert_eq!(line, format!("0 {:<33} {:<12} {:<15} {:<8} {:<20} {:<25} {:<6} up", "", "", "", "INFO", "", "", ""));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn sequence_is_only_written_when_the_template_has_it() {
+    let record = column_record();
+
+    let template = TextTemplate::parse("{timestamp} [{level}] {message}").unwrap();
+    assert_eq!(template.render(&record, &[]), "2025-01-15T10:30:45.123456+00:00 [WARNING] settlement delayed");
+    assert!(!template.starts_with_sequence());
+    assert!(TextTemplate::parse(DEFAULT_TEXT_TEMPLATE).unwrap().starts_with_sequence());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn default_template_renders_extras_only_when_configured() {
+    let mut record = column_record();
+    record.process_id = "4242".to_string();
+    let template = TextTemplate::parse(DEFAULT_TEXT_TEMPLATE).unwrap();
+    let line = template.render(&record, &[]);
+
+    assert!(line.ends_with(" settlement delayed"));
+    assert_eq!(
+        template.render(&record, &[LogField::Module, LogField::ProcessId]),
+        format!("{} | process_id=4242", line)
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_widths_truncation_alignment_and_literal_braces() {
+    let record = column_record();
+    let template = TextTemplate::parse("{{{level:>9}}} {hostname:.7}|{line_number:<4.2}|{seq:>5}").unwrap();
+
+    assert_eq!(template.render(&record, &[]), "{  WARNING} trading|12  |   42");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_invalid_templates() {
+    let error = |template: &str| TextTemplate::parse(template).unwrap_err();
+
+    assert_eq!(error("{seq} {colour}"), "unknown field 'colour' in template");
+    assert!(error("{message:abc}").starts_with("invalid format"));
+    assert!(error("{message:<12.x}").starts_with("invalid format"));
+    assert!(error("{message:<-3}").starts_with("invalid format"));
+    assert!(error("{message:.}").starts_with("invalid format"));
+    assert!(error("{extras:<10}").contains("does not accept a format"));
+    assert!(error("{seq} {message").starts_with("unclosed placeholder"));
+    assert!(error("{seq} message}").starts_with("unmatched '}'"));
+    assert!(error("{}").starts_with("unknown field"));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_field_lists() {
+    assert_eq!(LogField::parse_list("").unwrap(), []);
+    assert_eq!(LogField::parse_list(" process_id , stack_trace,").unwrap(), [LogField::ProcessId, LogField::StackTrace]);
+    assert_eq!(LogField::parse_list("process_id,colour").unwrap_err(), "unknown field 'colour'");
+    assert!(LogField::parse_list("extras").is_err());
+
+    for field in LogField::ALL {
+        assert_eq!(LogField::from_name(field.name()), Some(field));
+    }
+}
@@ -0,0 +1,273 @@
+//! Fluent Forward tests : Message / Forward / PackedForward decoding and stream framing
+
+use std::io::Write;
+
+use bytes::BytesMut;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log_server::core::forward::decode_forward;
+use log_server::core::records::AttributeValue;
+use log_server::network::forward_server::ForwardCodec;
+use rmpv::Value;
+use tokio_util::codec::Decoder;
+
+
+
+
+const SOURCE: &str = "10.0.0.9:24224";
+
+const MAX_BYTES: usize = 1024 * 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper building a `{key: value}` map
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(entries.iter().map(|(key, value)| (Value::from(*key), value.clone())).collect())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper encoding a value
+fn encode(value: &Value) -> Vec<u8> {
+    let mut data = Vec::new();
+    rmpv::encode::write_value(&mut data, value).unwrap();
+    data
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper building an EventTime extension
+fn event_time(seconds: u32, nanos: u32) -> Value {
+    Value::Ext(0, [seconds.to_be_bytes(), nanos.to_be_bytes()].concat())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_message_mode_with_event_time_and_chunk() {
+    let message = Value::Array(vec![
+        Value::from("app.orders"),
+        event_time(1736937045, 123_000_000),
+        map(&[
+            ("log", Value::from("order filled\n")),
+            ("level", Value::from("warn")),
+            ("host", Value::from("edge-01")),
+            ("qty", Value::from(150)),
+            ("price", Value::from(101.25)),
+            ("replay", Value::from(false)),
+        ]),
+        map(&[("chunk", Value::from("p8n9gmxTQVC8/nh2wlKKeQ=="))]),
+    ]);
+
+    let message = decode_forward(message, SOURCE, MAX_BYTES).unwrap();
+    assert_eq!(message.chunk.as_deref(), Some("p8n9gmxTQVC8/nh2wlKKeQ=="));
+    let record = &message.records[0];
+    assert_eq!(record.logger_name, "app.orders");
+    assert_eq!(record.timestamp, "2025-01-15T10:30:45.123Z");
+    assert_eq!(record.message, "order filled");
+    assert_eq!(record.level_name(), "WARNING");
+    assert_eq!(record.hostname, "edge-01");
+    assert_eq!(
+        record.attributes,
+        vec![
+            ("qty".to_string(), AttributeValue::Int(150)),
+            ("price".to_string(), AttributeValue::Float(101.25)),
+            ("replay".to_string(), AttributeValue::Bool(false)),
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_forward_mode_entries() {
+    let entries = (0..3)
+        .map(|index| Value::Array(vec![Value::from(1736937045 + index), map(&[("message", Value::from(format!("m{}", index)))])]))
+        .collect();
+    let message = Value::Array(vec![Value::from("app"), Value::Array(entries)]);
+
+    let message = decode_forward(message, SOURCE, MAX_BYTES).unwrap();
+    assert_eq!(message.chunk, None);
+    let messages: Vec<&str> = message.records.iter().map(|record| record.message.as_str()).collect();
+    assert_eq!(messages, ["m0", "m1", "m2"]);
+    assert_eq!(message.records[2].timestamp, "2025-01-15T10:30:47Z");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_compressed_packed_forward_entries() {
+    let mut packed = Vec::new();
+    for index in 0..2 {
+        packed.extend(encode(&Value::Array(vec![event_time(1736937045, 0), map(&[("msg", Value::from(index))])])));
+    }
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(&packed).unwrap();
+    let message = Value::Array(vec![
+        Value::from("app"),
+        Value::Binary(gzip.finish().unwrap()),
+        map(&[("size", Value::from(2)), ("compressed", Value::from("gzip"))]),
+    ]);
+
+    let message = decode_forward(message, SOURCE, MAX_BYTES).unwrap();
+    let messages: Vec<&str> = message.records.iter().map(|record| record.message.as_str()).collect();
+    assert_eq!(messages, ["0", "1"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn keeps_already_mapped_and_unknown_level_keys_as_attributes() {
+    let message = Value::Array(vec![
+        Value::from("app"),
+        Value::from(1736937045),
+        map(&[
+            ("message", Value::from("first")),
+            ("log", Value::from("second")),
+            ("level", Value::from(3)),
+        ]),
+    ]);
+
+    let record = &decode_forward(message, SOURCE, MAX_BYTES).unwrap().records[0];
+    assert_eq!(record.message, "first");
+    assert_eq!(record.level_name(), "NOTSET");
+    assert_eq!(
+        record.attributes,
+        vec![
+            ("log".to_string(), AttributeValue::String("second".to_string())),
+            ("level".to_string(), AttributeValue::Int(3)),
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_malformed_messages() {
+    let record = map(&[("message", Value::from("x"))]);
+    assert!(decode_forward(Value::from("app"), SOURCE, MAX_BYTES).is_err());
+    assert!(decode_forward(Value::Array(vec![Value::from(1), Value::Array(vec![])]), SOURCE, MAX_BYTES).is_err());
+    assert!(decode_forward(Value::Array(vec![Value::from("app"), Value::from(1)]), SOURCE, MAX_BYTES).is_err());
+    assert!(decode_forward(Value::Array(vec![Value::from("app"), Value::from(1), record, Value::from(2)]), SOURCE, MAX_BYTES).is_err());
+    let corrupt = Value::Array(vec![
+        Value::from("app"),
+        Value::Binary(b"not gzip".to_vec()),
+        map(&[("compressed", Value::from("gzip"))]),
+    ]);
+    assert!(decode_forward(corrupt, SOURCE, MAX_BYTES).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn skips_malformed_entries_and_keeps_the_chunk() {
+    let entry = |time: Value, message: &str| Value::Array(vec![time, map(&[("message", Value::from(message))])]);
+    let entries = vec![
+        entry(Value::from(1736937045), "first"),
+        entry(Value::from("bad time"), "lost"),
+        Value::Array(vec![Value::from(1736937045), Value::from("not a map")]),
+        Value::from("not an entry"),
+        entry(Value::from(1736937046), "last"),
+    ];
+    let chunk = map(&[("chunk", Value::from("Zm9yd2FyZA=="))]);
+    let message = Value::Array(vec![Value::from("app"), Value::Array(entries), chunk.clone()]);
+
+    let message = decode_forward(message, SOURCE, MAX_BYTES).unwrap();
+    let messages: Vec<&str> = message.records.iter().map(|record| record.message.as_str()).collect();
+    assert_eq!(messages, ["first", "last"]);
+    assert_eq!(message.skipped.len(), 3);
+    assert!(message.skipped[0].contains("invalid event time"));
+    assert_eq!(message.chunk.as_deref(), Some("Zm9yd2FyZA=="));
+
+    // Message mode : its single entry is skipped, the chunk is still acknowledged
+    let message = Value::Array(vec![Value::from("app"), Value::from(1), Value::from("not a map"), chunk]);
+    let message = decode_forward(message, SOURCE, MAX_BYTES).unwrap();
+    assert!(message.records.is_empty());
+    assert_eq!(message.skipped.len(), 1);
+    assert!(message.chunk.is_some());
+
+    // PackedForward : the entries before a truncated one are kept
+    let mut packed = encode(&entry(Value::from(1736937045), "packed"));
+    packed.extend(&encode(&entry(Value::from(1736937046), "truncated"))[..6]);
+    let message = decode_forward(Value::Array(vec![Value::from("app"), Value::Binary(packed)]), SOURCE, MAX_BYTES).unwrap();
+    assert_eq!(message.records.len(), 1);
+    assert_eq!(message.records[0].message, "packed");
+    assert_eq!(message.skipped.len(), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_stream_at_every_split() {
+    let first = Value::Array(vec![Value::from("a"), Value::from(1), map(&[("message", Value::from("one"))])]);
+    let second = Value::Array(vec![Value::from("b"), Value::Array(vec![])]);
+    // Every marker family : integers, floats, strings, binaries, extensions, long arrays and maps
+    let third = Value::Array(vec![
+        Value::from("c"),
+        Value::Array((0..20).map(|index| Value::from(index * 1000)).collect()),
+        Value::Map((0..20).map(|index| (Value::from(format!("k{}", index)), Value::from(-index))).collect()),
+        Value::Array(vec![
+            Value::from(u64::MAX),
+            Value::from(i64::MIN),
+            Value::F32(1.5),
+            Value::F64(2.5),
+            Value::Nil,
+            Value::from(true),
+            Value::from("s".repeat(40)),
+            Value::from("t".repeat(300)),
+            Value::Binary(vec![7; 300]),
+            Value::Ext(5, vec![1; 4]),
+            Value::Ext(6, vec![2; 3]),
+            event_time(1736937045, 0),
+        ]),
+    ]);
+    let stream = [encode(&first), encode(&second), encode(&third)].concat();
+
+    for split in 0..=stream.len() {
+        let mut codec = ForwardCodec::new(MAX_BYTES);
+        let mut buffer = BytesMut::new();
+        let mut values = Vec::new();
+        for chunk in [&stream[..split], &stream[split..]] {
+            buffer.extend_from_slice(chunk);
+            while let Some(value) = codec.decode(&mut buffer).unwrap() {
+                values.push(value);
+            }
+        }
+        assert_eq!(values, vec![first.clone(), second.clone(), third.clone()], "split at {}", split);
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_oversized_stream_message() {
+    let mut codec = ForwardCodec::new(16);
+    let message = encode(&Value::Array(vec![Value::from("app"), Value::from("x".repeat(64))]));
+
+    // The string header declares the size, rejected before its bytes arrive
+    let mut buffer = BytesMut::from(&message[..8]);
+    assert!(codec.decode(&mut buffer).is_err());
+
+    // An array declaring more items than bytes left
+    let mut codec = ForwardCodec::new(16);
+    let mut buffer = BytesMut::from(&[0xdc, 0x00, 0x20][..]);
+    assert!(codec.decode(&mut buffer).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_buffered_messages_larger_in_total_than_the_limit() {
+    let message = Value::Array(vec![Value::from("app"), Value::from(1), map(&[("message", Value::from("x"))])]);
+    let encoded = encode(&message);
+    let mut codec = ForwardCodec::new(encoded.len());
+    let mut buffer = BytesMut::from(&encoded.repeat(4)[..]);
+
+    for _ in 0..4 {
+        assert_eq!(codec.decode(&mut buffer).unwrap(), Some(message.clone()));
+    }
+    assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+    buffer.extend_from_slice(&[0xc1]);
+    assert!(codec.decode(&mut buffer).is_err());
+}
@@ -0,0 +1,198 @@
+//! TCP framing tests : frames split at every byte boundary must decode identically
+
+use std::time::Duration;
+
+use bytes::BytesMut;
+use log_server::common::safe_socket::{FrameCodec, SafeSocket};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio_util::codec::{Decoder, Encoder};
+
+
+
+
+const MAX_FRAME_BYTES: usize = 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build the payloads under test, including an empty one and one longer than 255 bytes
+fn payloads() -> Vec<Vec<u8>> {
+    vec![
+        b"first frame".to_vec(),
+        Vec::new(),
+        (0..=255u8).chain(0..=44u8).collect(),
+        vec![0xff; 5],
+    ]
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to encode payloads back to back as one stream
+fn encode_stream(payloads: &[Vec<u8>]) -> Vec<u8> {
+    let mut codec = FrameCodec::new(MAX_FRAME_BYTES);
+    let mut stream = BytesMut::new();
+    for payload in payloads {
+        codec.encode(payload.as_slice(), &mut stream).unwrap();
+    }
+    stream.to_vec()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to feed `chunks` to one decoder in order, collecting every decoded frame
+fn decode_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Vec<Vec<u8>> {
+    let mut codec = FrameCodec::new(MAX_FRAME_BYTES);
+    let mut buffer = BytesMut::new();
+    let mut frames = Vec::new();
+    for chunk in chunks {
+        buffer.extend_from_slice(chunk);
+        while let Some(frame) = codec.decode(&mut buffer).unwrap() {
+            frames.push(frame.to_vec());
+        }
+    }
+    assert!(buffer.is_empty(), "bytes left over after the last frame");
+    frames
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_stream_split_in_two_at_every_byte_boundary() {
+    let payloads = payloads();
+    let stream = encode_stream(&payloads);
+
+    for split in 0..=stream.len() {
+        let (head, tail) = stream.split_at(split);
+        assert_eq!(decode_chunks([head, tail]), payloads, "split at byte {}", split);
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_stream_split_in_three_at_every_pair_of_boundaries() {
+    let payloads = payloads();
+    let stream = encode_stream(&payloads);
+
+    for first in 0..=stream.len() {
+        for second in first..=stream.len() {
+            let chunks = [&stream[..first], &stream[first..second], &stream[second..]];
+            assert_eq!(decode_chunks(chunks), payloads, "split at bytes {} and {}", first, second);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_stream_fed_one_byte_at_a_time() {
+    let payloads = payloads();
+    let stream = encode_stream(&payloads);
+
+    assert_eq!(decode_chunks(stream.chunks(1)), payloads);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_oversized_frame_from_its_prefix_alone() {
+    let mut codec = FrameCodec::new(MAX_FRAME_BYTES);
+    let mut buffer = BytesMut::new();
+
+    for byte in ((MAX_FRAME_BYTES + 1) as u32).to_be_bytes() {
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+        buffer.extend_from_slice(&[byte]);
+    }
+    let error = codec.decode(&mut buffer).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn accepts_frame_of_exactly_the_maximum_size() {
+    let payload = vec![7u8; MAX_FRAME_BYTES];
+    let stream = encode_stream(std::slice::from_ref(&payload));
+    assert_eq!(decode_chunks([stream.as_slice()]), vec![payload]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn socket_reassembles_frames_split_across_tcp_writes() {
+    let payloads = payloads();
+    let stream = encode_stream(&payloads);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = tokio::spawn(async move {
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        conn.set_nodelay(true).unwrap();
+        // Split inside the first length prefix, then inside a payload
+        for chunk in [&stream[..2], &stream[2..9], &stream[9..]] {
+            conn.write_all(chunk).await.unwrap();
+            conn.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let (conn, _) = listener.accept().await.unwrap();
+    let mut socket = SafeSocket::new(conn, MAX_FRAME_BYTES);
+    let mut frames = Vec::new();
+    while let Some(frame) = socket.receive_data().await.unwrap() {
+        frames.push(frame.to_vec());
+    }
+    client.await.unwrap();
+
+    assert_eq!(frames, payloads);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn socket_reassembles_frames_split_across_unix_socket_writes() {
+    let payloads = payloads();
+    let stream = encode_stream(&payloads);
+
+    let path = std::env::temp_dir().join(format!("log_server_framing_{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let client_path = path.clone();
+    let client = tokio::spawn(async move {
+        let mut conn = UnixStream::connect(client_path).await.unwrap();
+        for chunk in [&stream[..2], &stream[2..9], &stream[9..]] {
+            conn.write_all(chunk).await.unwrap();
+            conn.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let (conn, _) = listener.accept().await.unwrap();
+    let mut socket = SafeSocket::new(conn, MAX_FRAME_BYTES);
+    let mut frames = Vec::new();
+    while let Some(frame) = socket.receive_data().await.unwrap() {
+        frames.push(frame.to_vec());
+    }
+    client.await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(frames, payloads);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn socket_reports_connection_closed_mid_frame() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = tokio::spawn(async move {
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        conn.write_all(&[0, 0, 0, 10, 1, 2, 3]).await.unwrap();
+    });
+
+    let (conn, _) = listener.accept().await.unwrap();
+    let mut socket = SafeSocket::new(conn, MAX_FRAME_BYTES);
+    client.await.unwrap();
+
+    assert!(socket.receive_data().await.is_err());
+}
\ No newline at end of file
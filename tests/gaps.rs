@@ -0,0 +1,147 @@
+//! Gap timeout tests : a missing sequence is skipped, late records are still written, nothing is lost
+
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use log_server::core::records::LogRecord;
+use log_server::core::writers::{AckStage, LogWriter, WriterHandle};
+use log_server::WriterConfig;
+use tokio::time::Instant;
+
+
+
+
+const SOURCE: &str = "10.0.0.7:9020";
+const GAP_TIMEOUT: Duration = Duration::from_secs(5);
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to start a writer on `dir/_main.log` with the test gap timeout
+async fn start_writer(dir: &Path) -> WriterHandle {
+    let config = WriterConfig {
+        log_path: dir.join("_main.log"),
+        gap_timeout: Some(GAP_TIMEOUT),
+        ..WriterConfig::default()
+    };
+    LogWriter::new(config).await.unwrap().start_writer_task()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a record carrying `message`
+fn record(message: &str) -> LogRecord {
+    let mut record = LogRecord::new(SOURCE);
+    record.message = message.to_string();
+    record
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper reading the log as (leading sequence, last word) pairs
+fn written(dir: &Path) -> Vec<(u64, String)> {
+    std::fs::read_to_string(dir.join("_main.log"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let sequence = line.split_whitespace().next().unwrap().parse().unwrap();
+            (sequence, line.split_whitespace().last().unwrap().to_string())
+        })
+        .collect()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test(start_paused = true)]
+async fn skips_a_missing_sequence_after_the_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path()).await;
+
+    handle.send_and_wait(record("first"), AckStage::Written).await.unwrap();
+    let lost = handle.skip_sequence();
+    let started = Instant::now();
+    let after_gap = handle.send_and_wait(record("second"), AckStage::Written).await.unwrap();
+
+    // The record behind the gap waited for the timeout, not longer
+    assert_eq!(started.elapsed(), GAP_TIMEOUT);
+    assert_eq!((lost, after_gap), (1, 2));
+    assert_eq!(handle.stats().skipped_sequences.load(Ordering::Relaxed), 1);
+    assert_eq!(
+        written(dir.path()),
+        [(0, "first".to_string()), (1, "----".to_string()), (2, "second".to_string())]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test(start_paused = true)]
+async fn writes_a_late_record_after_its_gap_was_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path()).await;
+
+    let late = handle.skip_sequence();
+    handle.send_and_wait(record("on-time"), AckStage::Written).await.unwrap();
+    let started = Instant::now();
+    handle
+        .queue_with_sequence(record("late"), late, AckStage::Written)
+        .await
+        .unwrap()
+        .wait()
+        .await
+        .unwrap();
+
+    // Written at once, out of order, rather than held or dropped
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert_eq!(handle.stats().late_records.load(Ordering::Relaxed), 1);
+    assert_eq!(
+        written(dir.path()),
+        [(0, "----".to_string()), (1, "on-time".to_string()), (0, "late".to_string())]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test(start_paused = true)]
+async fn drops_no_record_around_gaps() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = start_writer(dir.path()).await;
+
+    // Two gaps, records queued behind them, then the missing records arriving late
+    let mut pending = Vec::new();
+    let mut lost = Vec::new();
+    for i in 0..10 {
+        if i == 3 || i == 7 {
+            lost.push(handle.skip_sequence());
+        }
+        pending.push(handle.queue(record(&format!("record-{}", i)), AckStage::Written).await.unwrap());
+    }
+    for ack in pending {
+        ack.wait().await.unwrap();
+    }
+    for sequence in lost {
+        handle
+            .queue_with_sequence(record(&format!("late-{}", sequence)), sequence, AckStage::Written)
+            .await
+            .unwrap()
+            .wait()
+            .await
+            .unwrap();
+    }
+
+    let lines = written(dir.path());
+    let messages: Vec<&str> = lines.iter().map(|(_, message)| message.as_str()).collect();
+    for i in 0..10 {
+        assert!(messages.contains(&format!("record-{}", i).as_str()), "record-{} missing", i);
+    }
+    assert!(messages.contains(&"late-3") && messages.contains(&"late-8"), "{:?}", messages);
+    assert_eq!(handle.stats().skipped_sequences.load(Ordering::Relaxed), 2);
+    assert_eq!(handle.stats().late_records.load(Ordering::Relaxed), 2);
+
+    // Apart from the late records, sequences are written in order
+    let in_order: Vec<u64> = lines
+        .iter()
+        .filter(|(_, message)| !message.starts_with("late-"))
+        .map(|(sequence, _)| *sequence)
+        .collect();
+    assert_eq!(in_order, (0..12).collect::<Vec<u64>>());
+}
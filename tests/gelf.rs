@@ -0,0 +1,166 @@
+//! GELF tests : payload parsing, compression and UDP chunk reassembly
+
+use std::io::Write;
+use std::time::Instant;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use log_server::core::gelf::parse_gelf;
+use log_server::core::records::AttributeValue;
+use log_server::network::gelf_server::{GelfChunks, CHUNK_TIMEOUT, MAX_PENDING_BYTES};
+
+
+
+
+const SOURCE: &str = "10.0.0.12:12201";
+
+const MAX_BYTES: usize = 1024 * 1024;
+
+const MESSAGE: &[u8] = br#"{"version":"1.1","host":"pay-01","short_message":"NullPointerException in settle","full_message":"java.lang.NullPointerException\n\tat Settle.run(Settle.java:42)","timestamp":1736937045.123,"level":3,"_LoggerName":"com.bank.Settle","_Thread":"pool-1-thread-3","_order_id":"A-17","_amount":250.5,"_attempt":2}"#;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper splitting `payload` into GELF chunks of `size` bytes with message id `id`
+fn chunks(payload: &[u8], id: u8, size: usize) -> Vec<Vec<u8>> {
+    let parts: Vec<&[u8]> = payload.chunks(size).collect();
+    parts
+        .iter()
+        .enumerate()
+        .map(|(sequence, part)| {
+            let mut chunk = vec![0x1e, 0x0f, id, 0, 0, 0, 0, 0, 0, 0, sequence as u8, parts.len() as u8];
+            chunk.extend_from_slice(part);
+            chunk
+        })
+        .collect()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_message_and_additional_fields() {
+    let record = parse_gelf(MESSAGE, SOURCE, MAX_BYTES).unwrap();
+
+    assert_eq!(record.hostname, "pay-01");
+    assert_eq!(record.message, "NullPointerException in settle");
+    assert_eq!(record.stack_trace, "java.lang.NullPointerException\n\tat Settle.run(Settle.java:42)");
+    assert_eq!(record.timestamp, "2025-01-15T10:30:45.123Z");
+    assert_eq!(record.level_name(), "ERROR");
+    assert_eq!(record.logger_name, "com.bank.Settle");
+    assert_eq!(record.thread_name, "pool-1-thread-3");
+    assert_eq!(
+        record.attributes,
+        vec![
+            ("order_id".to_string(), AttributeValue::String("A-17".to_string())),
+            ("amount".to_string(), AttributeValue::Float(250.5)),
+            ("attempt".to_string(), AttributeValue::Int(2)),
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_zlib_and_gzip_compressed_messages() {
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(MESSAGE).unwrap();
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(MESSAGE).unwrap();
+
+    for payload in [zlib.finish().unwrap(), gzip.finish().unwrap()] {
+        let record = parse_gelf(&payload, SOURCE, MAX_BYTES).unwrap();
+        assert_eq!(record.message, "NullPointerException in settle");
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn falls_back_on_sender_host_and_rejects_invalid_messages() {
+    let record = parse_gelf(br#"{"version":"1.1","short_message":"up"}"#, SOURCE, MAX_BYTES).unwrap();
+    assert_eq!(record.hostname, "10.0.0.12");
+    assert_eq!(record.level_name(), "NOTSET");
+
+    assert!(parse_gelf(br#"{"version":"1.1","host":"h"}"#, SOURCE, MAX_BYTES).is_err());
+    assert!(parse_gelf(br#"{"short_message":"x","level":9}"#, SOURCE, MAX_BYTES).is_err());
+    assert!(parse_gelf(br#"{"short_message":"x","timestamp":"yesterday"}"#, SOURCE, MAX_BYTES).is_err());
+    assert!(parse_gelf(b"[1,2]", SOURCE, MAX_BYTES).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn reassembles_chunks_in_any_order() {
+    let mut reassembly = GelfChunks::new(MAX_BYTES, MAX_PENDING_BYTES);
+    let now = Instant::now();
+    let mut parts = chunks(MESSAGE, 7, 64);
+    parts.reverse();
+    let duplicate = parts[1].clone();
+
+    let last = parts.pop().unwrap();
+    for part in parts {
+        assert_eq!(reassembly.add(&part, now).unwrap(), None);
+    }
+    assert_eq!(reassembly.add(&duplicate, now).unwrap(), None);
+    assert_eq!(reassembly.add(&last, now).unwrap().as_deref(), Some(MESSAGE));
+    assert_eq!(reassembly.add(MESSAGE, now).unwrap().as_deref(), Some(MESSAGE));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn expires_incomplete_chunked_messages() {
+    let mut reassembly = GelfChunks::new(MAX_BYTES, MAX_PENDING_BYTES);
+    let now = Instant::now();
+    let parts = chunks(MESSAGE, 9, 64);
+
+    assert_eq!(reassembly.add(&parts[0], now).unwrap(), None);
+    assert_eq!(reassembly.expire(now + CHUNK_TIMEOUT / 2), 0);
+    assert_eq!(reassembly.expire(now + CHUNK_TIMEOUT), 1);
+    // The late chunk starts a new message that never completes
+    assert_eq!(reassembly.add(&parts[1], now + CHUNK_TIMEOUT).unwrap(), None);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_invalid_and_oversized_chunks() {
+    let mut reassembly = GelfChunks::new(100, MAX_PENDING_BYTES);
+    let now = Instant::now();
+
+    assert!(reassembly.add(&[0x1e, 0x0f, 1, 2], now).is_err());
+    assert!(reassembly.add(&[0x1e, 0x0f, 1, 0, 0, 0, 0, 0, 0, 0, 3, 3], now).is_err());
+    assert!(reassembly.add(&[0x1e, 0x0f, 1, 0, 0, 0, 0, 0, 0, 0, 0, 129], now).is_err());
+
+    let parts = chunks(MESSAGE, 3, 64);
+    assert_eq!(reassembly.add(&parts[0], now).unwrap(), None);
+    assert!(reassembly.add(&parts[1], now).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn caps_the_bytes_held_for_messages_in_progress() {
+    let mut reassembly = GelfChunks::new(MAX_BYTES, 320);
+    let now = Instant::now();
+    let first = chunks(MESSAGE, 1, 64);
+    let second = chunks(MESSAGE, 2, 64);
+
+    for part in [&first[0], &first[1], &second[0], &second[1], &second[2]] {
+        assert_eq!(reassembly.add(part, now).unwrap(), None);
+    }
+    assert_eq!(reassembly.pending_bytes(), 320);
+
+    // The chunk over the limit is rejected with its message, the other one is kept
+    assert!(reassembly.add(&second[3], now).is_err());
+    assert_eq!(reassembly.pending_bytes(), 128);
+
+    // Completed and expired messages release their bytes
+    for part in &first[2..4] {
+        assert_eq!(reassembly.add(part, now).unwrap(), None);
+    }
+    assert_eq!(reassembly.add(&first[4], now).unwrap().as_deref(), Some(MESSAGE));
+    assert_eq!(reassembly.pending_bytes(), 0);
+    assert_eq!(reassembly.add(&second[0], now).unwrap(), None);
+    assert_eq!(reassembly.expire(now + CHUNK_TIMEOUT), 1);
+    assert_eq!(reassembly.pending_bytes(), 0);
+}
@@ -0,0 +1,74 @@
+//! gRPC tests : LogMessage status codes for client errors and writer failures
+
+use log_server::common::config::ServerConfig;
+use log_server::core::writers::WriterHandle;
+use log_server::network::grpc_server::log_service::log_service_server::LogService;
+use log_server::network::grpc_server::log_service::{Attribute, LogRequest};
+use log_server::network::grpc_server::GrpcLogServiceImpl;
+use tonic::{Code, Request};
+
+
+
+
+// Helper to build a valid INFO log request
+fn log_request(message: &str) -> LogRequest {
+    LogRequest {
+        level: 3,
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn answers_success_once_queued() {
+    let config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    let (writer, mut records) = WriterHandle::detached(8);
+    let service = GrpcLogServiceImpl::new(&config, writer);
+
+    let response = service.log_message(Request::new(log_request("order settled"))).await.unwrap();
+    assert!(response.into_inner().success);
+    assert_eq!(records.try_recv().unwrap().message, "order settled");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rejects_invalid_messages_as_invalid_argument() {
+    let config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    let (writer, mut records) = WriterHandle::detached(8);
+    let service = GrpcLogServiceImpl::new(&config, writer);
+
+    let invalid_level = LogRequest {
+        level: 42,
+        ..log_request("bad level")
+    };
+    let keyless_attribute = LogRequest {
+        attributes: vec![Attribute {
+            key: String::new(),
+            value: None,
+        }],
+        ..log_request("bad attribute")
+    };
+
+    for request in [invalid_level, keyless_attribute] {
+        let status = service.log_message(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument, "{}", status.message());
+    }
+    assert!(records.try_recv().is_none());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn reports_writer_failures_as_internal() {
+    let config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    let (writer, records) = WriterHandle::detached(8);
+    let service = GrpcLogServiceImpl::new(&config, writer);
+
+    // Writer gone : a valid message cannot be queued
+    drop(records);
+    let status = service.log_message(Request::new(log_request("lost"))).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+}
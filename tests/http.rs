@@ -0,0 +1,148 @@
+//! HTTP tests : `POST /v1/logs` body splitting, per-item errors, token authentication and body limit
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use log_server::common::auth::TokenAuth;
+use log_server::common::config::ServerConfig;
+use log_server::common::stats::ServerStats;
+use log_server::core::writers::{QueuedRecords, WriterHandle};
+use log_server::network::http_server::HttpServer;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+
+
+
+const MAX_BODY_BYTES: usize = 1024;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to start an HTTP server over a detached writer queue, returns its address
+async fn start(auth: Option<&str>) -> (SocketAddr, QueuedRecords, Arc<ServerStats>) {
+    let mut config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    config.max_frame_bytes = MAX_BODY_BYTES;
+    config.auth = auth.map(|tokens| Arc::new(TokenAuth::parse(tokens).unwrap()));
+    let (writer, records) = WriterHandle::detached(64);
+    let stats = Arc::new(ServerStats::default());
+    let server = HttpServer::new(&config, writer, stats.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = server.serve(listener).await;
+    });
+    (addr, records, stats)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to post `body` with extra header lines, returns the status and the raw response body
+async fn post(addr: SocketAddr, headers: &[&str], body: &str) -> (u16, String) {
+    let mut request = format!(
+        "POST /v1/logs HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        addr,
+        body.len()
+    );
+    for header in headers {
+        request.push_str(header);
+        request.push_str("\r\n");
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to take every queued record message
+fn messages(records: &mut QueuedRecords) -> Vec<String> {
+    std::iter::from_fn(|| records.try_recv()).map(|record| record.message).collect()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn answers_per_item_errors_for_a_json_array() {
+    let (addr, mut records, _) = start(None).await;
+    let body = json!([
+        {"message": "first", "level": "warning", "line_number": 42},
+        {"message": "second", "level": "LOUD"},
+        {"message": "third", "colour": "red"},
+        {"message": "fourth", "level": 3},
+    ]);
+
+    let (status, response) = post(addr, &[], &body.to_string()).await;
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["success"], false);
+    assert_eq!(response["accepted"], 2);
+    assert_eq!(response["rejected"], 2);
+    assert_eq!(response["errors"][0]["index"], 1);
+    assert_eq!(response["errors"][1]["index"], 2);
+    assert!(response["errors"][1]["error"].as_str().unwrap().contains("unknown field"));
+    assert_eq!(messages(&mut records), ["first", "fourth"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn splits_ndjson_lines_with_or_without_content_type() {
+    let (addr, mut records, _) = start(None).await;
+
+    let body = "{\"message\":\"one\"}\nnot json\n\n{\"message\":\"two\"}\n";
+    let (status, response) = post(addr, &["Content-Type: application/x-ndjson"], body).await;
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!((response["accepted"].as_u64(), response["rejected"].as_u64()), (Some(2), Some(1)));
+    assert_eq!(response["errors"][0]["index"], 1);
+    assert_eq!(messages(&mut records), ["one", "two"]);
+
+    // Several JSON values are read as NDJSON without the content type too
+    let (status, _) = post(addr, &["Content-Type: application/json"], "{\"message\":\"a\"}\n{\"message\":\"b\"}").await;
+    assert_eq!(status, 200);
+    assert_eq!(messages(&mut records), ["a", "b"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rejects_unreadable_and_oversized_bodies() {
+    let (addr, mut records, _) = start(None).await;
+
+    assert_eq!(post(addr, &[], "{\"message\":").await.0, 400);
+    assert_eq!(post(addr, &[], "42").await.0, 400);
+    assert_eq!(post(addr, &[], "").await.0, 400);
+
+    let oversized = json!({ "message": "x".repeat(MAX_BODY_BYTES) }).to_string();
+    assert_eq!(post(addr, &[], &oversized).await.0, 413);
+    assert!(messages(&mut records).is_empty());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn requires_a_known_bearer_token() {
+    let (addr, mut records, stats) = start(Some("trader-01 s3cret-token")).await;
+    let body = "{\"message\":\"order filled\"}";
+
+    assert_eq!(post(addr, &[], body).await.0, 401);
+    assert_eq!(post(addr, &["Authorization: Bearer wrong-token"], body).await.0, 401);
+    assert_eq!(stats.auth_failures.load(Ordering::Relaxed), 2);
+    assert!(records.try_recv().is_none());
+
+    assert_eq!(post(addr, &["Authorization: Bearer s3cret-token"], body).await.0, 200);
+    let record = records.try_recv().unwrap();
+    assert_eq!(record.client, "trader-01");
+    assert_eq!(record.source.split(':').next(), Some("127.0.0.1"));
+}
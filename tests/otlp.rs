@@ -0,0 +1,246 @@
+//! OTLP tests : resource, scope and log record mapping, severity mapping and value flattening
+
+use log_server::core::otlp::{records_from_otlp, severity_level};
+use log_server::core::records::{AttributeValue, LogRecord};
+use log_server::logger_capnp::logger_msg::Level;
+use log_server::network::grpc_server::opentelemetry::proto::common::v1::{
+    any_value, AnyValue, ArrayValue, InstrumentationScope, KeyValue, KeyValueList,
+};
+use log_server::network::grpc_server::opentelemetry::proto::logs::v1::{
+    LogRecord as OtlpLogRecord, ResourceLogs, ScopeLogs,
+};
+use log_server::network::grpc_server::opentelemetry::proto::resource::v1::Resource;
+
+
+
+
+const SOURCE: &str = "10.0.0.20:4317";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build an OTLP attribute
+fn attribute(key: &str, value: any_value::Value) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue { value: Some(value) }),
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a string OTLP value
+fn text(value: &str) -> any_value::Value {
+    any_value::Value::StringValue(value.to_string())
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper wrapping log records in one resource and one instrumentation scope
+fn resource_logs(resource: Vec<KeyValue>, scope: &str, log_records: Vec<OtlpLogRecord>) -> ResourceLogs {
+    ResourceLogs {
+        resource: Some(Resource {
+            attributes: resource,
+            ..Default::default()
+        }),
+        scope_logs: vec![ScopeLogs {
+            scope: Some(InstrumentationScope {
+                name: scope.to_string(),
+                ..Default::default()
+            }),
+            log_records,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper converting a single log record without resource attributes
+fn convert(log_record: OtlpLogRecord) -> LogRecord {
+    let mut records = records_from_otlp(resource_logs(Vec::new(), "", vec![log_record]), SOURCE);
+    assert_eq!(records.len(), 1);
+    records.remove(0).unwrap()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn maps_resource_and_scope_onto_schema_fields() {
+    let resource = vec![
+        attribute("service.name", text("payments")),
+        attribute("host.name", text("pay-01")),
+        attribute("process.pid", any_value::Value::IntValue(4242)),
+        attribute("deployment.environment", text("prod")),
+    ];
+    let log_records = vec![
+        OtlpLogRecord {
+            body: Some(AnyValue { value: Some(text("order settled")) }),
+            attributes: vec![attribute("order_id", text("A-17"))],
+            ..Default::default()
+        },
+        OtlpLogRecord {
+            body: Some(AnyValue { value: Some(text("order rejected")) }),
+            ..Default::default()
+        },
+    ];
+
+    let records: Vec<LogRecord> = records_from_otlp(resource_logs(resource, "com.bank.Settle", log_records), SOURCE)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    for record in &records {
+        assert_eq!(record.service_name, "payments");
+        assert_eq!(record.hostname, "pay-01");
+        assert_eq!(record.process_id, "4242");
+        assert_eq!(record.logger_name, "com.bank.Settle");
+        assert_eq!(record.source, SOURCE);
+    }
+    assert_eq!(records[0].message, "order settled");
+    assert_eq!(
+        records[0].attributes,
+        vec![
+            ("deployment.environment".to_string(), AttributeValue::String("prod".to_string())),
+            ("order_id".to_string(), AttributeValue::String("A-17".to_string())),
+        ]
+    );
+    assert_eq!(records[1].attributes.len(), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn maps_log_record_fields_and_trace_context() {
+    let record = convert(OtlpLogRecord {
+        time_unix_nano: 1_736_937_045_123_000_000,
+        severity_number: 17,
+        body: Some(AnyValue { value: Some(text("settlement failed")) }),
+        attributes: vec![
+            attribute("thread.name", text("pool-1-thread-3")),
+            attribute("code.filepath", text("/app/settle/Settle.java")),
+            attribute("code.lineno", any_value::Value::IntValue(42)),
+            attribute("exception.stacktrace", text("java.lang.NullPointerException")),
+        ],
+        trace_id: vec![0xab; 16],
+        span_id: vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+        ..Default::default()
+    });
+
+    assert_eq!(record.timestamp, "2025-01-15T10:30:45.123Z");
+    assert_eq!(record.level_name(), "ERROR");
+    assert_eq!(record.message, "settlement failed");
+    assert_eq!(record.thread_name, "pool-1-thread-3");
+    assert_eq!(record.path_name, "/app/settle/Settle.java");
+    assert_eq!(record.filename, "Settle.java");
+    assert_eq!(record.line_number, "42");
+    assert_eq!(record.stack_trace, "java.lang.NullPointerException");
+    assert_eq!(
+        record.attributes,
+        vec![
+            ("trace_id".to_string(), AttributeValue::String("ab".repeat(16))),
+            ("span_id".to_string(), AttributeValue::String("0102030405060708".to_string())),
+        ]
+    );
+
+    // Without a time, the observed time is used
+    let record = convert(OtlpLogRecord {
+        observed_time_unix_nano: 1_736_937_045_000_000_000,
+        ..Default::default()
+    });
+    assert_eq!(record.timestamp, "2025-01-15T10:30:45Z");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn maps_severity_numbers_then_severity_text() {
+    assert_eq!(severity_level(1, ""), Level::Debug);
+    assert_eq!(severity_level(8, ""), Level::Debug);
+    assert_eq!(severity_level(9, "WARN"), Level::Info);
+    assert_eq!(severity_level(13, ""), Level::Warning);
+    assert_eq!(severity_level(17, ""), Level::Error);
+    assert_eq!(severity_level(21, ""), Level::Critical);
+    assert_eq!(severity_level(24, ""), Level::Critical);
+
+    // Unspecified or out of range numbers fall back on the text
+    assert_eq!(severity_level(0, "warn"), Level::Warning);
+    assert_eq!(severity_level(0, "FATAL"), Level::Critical);
+    assert_eq!(severity_level(0, "TRACE"), Level::Debug);
+    assert_eq!(severity_level(25, "trade"), Level::Trade);
+    assert_eq!(severity_level(0, "verbose"), Level::Notset);
+    assert_eq!(severity_level(0, ""), Level::Notset);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn flattens_structured_values() {
+    let list = any_value::Value::KvlistValue(KeyValueList {
+        values: vec![
+            attribute("id", text("A-17")),
+            attribute("amount", any_value::Value::DoubleValue(250.5)),
+        ],
+    });
+    let array = any_value::Value::ArrayValue(ArrayValue {
+        values: vec![
+            AnyValue { value: Some(any_value::Value::IntValue(1)) },
+            AnyValue { value: Some(any_value::Value::BoolValue(true)) },
+            AnyValue { value: None },
+        ],
+    });
+    let record = convert(OtlpLogRecord {
+        body: Some(AnyValue { value: Some(list.clone()) }),
+        attributes: vec![
+            attribute("order", list),
+            attribute("retries", array),
+            attribute("payload", any_value::Value::BytesValue(vec![0xde, 0xad])),
+            attribute("latency_ms", any_value::Value::DoubleValue(1.5)),
+            attribute("final", any_value::Value::BoolValue(false)),
+            KeyValue {
+                key: "empty".to_string(),
+                value: None,
+            },
+        ],
+        ..Default::default()
+    });
+
+    assert_eq!(record.message, r#"{"id":"A-17","amount":250.5}"#);
+    assert_eq!(
+        record.attributes,
+        vec![
+            ("order".to_string(), AttributeValue::String(r#"{"id":"A-17","amount":250.5}"#.to_string())),
+            ("retries".to_string(), AttributeValue::String("[1,true,null]".to_string())),
+            ("payload".to_string(), AttributeValue::String("dead".to_string())),
+            ("latency_ms".to_string(), AttributeValue::Float(1.5)),
+            ("final".to_string(), AttributeValue::Bool(false)),
+            ("empty".to_string(), AttributeValue::String(String::new())),
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_attributes_without_a_key() {
+    let log_records = vec![
+        OtlpLogRecord {
+            attributes: vec![attribute("", text("lost"))],
+            ..Default::default()
+        },
+        OtlpLogRecord {
+            attributes: vec![attribute("order_id", text("A-17"))],
+            ..Default::default()
+        },
+    ];
+    let records = records_from_otlp(resource_logs(Vec::new(), "", log_records.clone()), SOURCE);
+    assert_eq!(records[0].as_ref().unwrap_err(), "attribute without a key");
+    assert!(records[1].is_ok());
+
+    // A resource attribute without a key rejects every record of the resource
+    let resource = vec![attribute("", text("lost"))];
+    let records = records_from_otlp(resource_logs(resource, "", log_records), SOURCE);
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(Result::is_err));
+}
@@ -0,0 +1,76 @@
+//! Resume tests : sequence numbering continued from the log left by a previous run
+
+use std::path::Path;
+
+use log_server::core::compression::Compression;
+use log_server::core::records::LogRecord;
+use log_server::core::writers::LogWriter;
+use log_server::WriterConfig;
+
+
+
+
+const SOURCE: &str = "10.0.0.4:9020";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to start a resuming writer on `dir/_main.log` and return the sequence of its first record
+async fn first_sequence(dir: &Path) -> Result<u64, String> {
+    let config = WriterConfig {
+        log_path: dir.join("_main.log"),
+        resume_sequence: true,
+        ..WriterConfig::default()
+    };
+    let writer = LogWriter::new(config).await.map_err(|e| e.to_string())?;
+    writer.start_writer_task().send(LogRecord::new(SOURCE)).await
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn resumes_after_the_highest_sequence_of_the_current_log() {
+    let dir = tempfile::tempdir().unwrap();
+    // A late record was written after 9, a message spans two lines
+    std::fs::write(dir.path().join("_main.log"), "5 a\n9 b\n  continued\n7 c\n").unwrap();
+
+    assert_eq!(first_sequence(dir.path()).await, Ok(10));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn resumes_from_a_compressed_backup_when_the_current_log_is_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let backup = dir.path().join("_main.2025-01-15.log");
+    std::fs::write(&backup, "3 a\n8 b\n").unwrap();
+    Compression::Gzip.compress_file(&backup).unwrap();
+    std::fs::write(dir.path().join("_main.log"), "").unwrap();
+
+    // A newer archive left half written by an interrupted compression is not a backup
+    std::fs::write(dir.path().join("_main.2025-01-16.log.zst.part"), b"\x28\xb5").unwrap();
+
+    assert_eq!(first_sequence(dir.path()).await, Ok(9));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn starts_at_zero_without_a_previous_log() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(first_sequence(dir.path()).await, Ok(0));
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("_main.log"), "").unwrap();
+    assert_eq!(first_sequence(dir.path()).await, Ok(0));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn refuses_a_log_without_sequence_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("_main.log"), "no sequence here\n").unwrap();
+
+    let error = first_sequence(dir.path()).await.unwrap_err();
+    assert!(error.contains("cannot resume the sequence"), "{}", error);
+}
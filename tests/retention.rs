@@ -0,0 +1,129 @@
+//! Retention tests : age limit, files retention never touches, archive dates and backup count
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use chrono::{TimeZone, Utc};
+use log_server::core::compression::Compression;
+use log_server::core::rotation::{RetentionPolicy, RotationInterval, Rotator};
+use log_server::WriterConfig;
+
+
+
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to create `dir/name` last modified `age` ago
+fn create_file(dir: &Path, name: &str, age: Duration) {
+    let path = dir.join(name);
+    std::fs::write(&path, "0 a\n").unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to list the file names of a directory, sorted
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to rotate `dir/_main.log` `count` times within one period
+async fn rotate_times(dir: &Path, interval: Option<RotationInterval>, backup_count: usize, count: usize) {
+    let config = WriterConfig {
+        rotation_interval: interval,
+        backup_count,
+        ..WriterConfig::default()
+    };
+    let now = Utc.with_ymd_and_hms(2025, 1, 15, 10, 30, 0).unwrap();
+    let mut rotator = Rotator::new(&dir.join("_main.log"), &config, Some(now));
+    for i in 0..count {
+        std::fs::write(dir.join("_main.log"), format!("{} a\n", i)).unwrap();
+        let rotated = rotator.rotate(now).await.unwrap();
+        assert_eq!(rotated.is_some(), backup_count > 0);
+        rotator.wait_background().await;
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn removes_only_rotated_files_older_than_max_age() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = 3 * DAY;
+    create_file(dir.path(), "_main.log", old);
+    create_file(dir.path(), "_main.log.0", old);
+    create_file(dir.path(), "_main.log.1.gz", DAY / 2);
+    create_file(dir.path(), "_main.2025-01-15.log", old);
+    create_file(dir.path(), "_main.2025-01-15T10.1.log.zst", old);
+    create_file(dir.path(), "_main.2025-01-16.log", DAY / 2);
+    create_file(dir.path(), "_main.2025-01-17.log.gz.part", old);
+    create_file(dir.path(), "_main.notes.txt", old);
+    create_file(dir.path(), "other.log", old);
+    create_file(dir.path(), "other.log.0", old);
+
+    let policy = RetentionPolicy {
+        max_age: Some(2 * DAY),
+        ..RetentionPolicy::default()
+    };
+    policy.enforce(&dir.path().join("_main.log")).await.unwrap();
+
+    assert_eq!(
+        file_names(dir.path()),
+        [
+            "_main.2025-01-16.log",
+            "_main.2025-01-17.log.gz.part",
+            "_main.log",
+            "_main.log.1.gz",
+            "_main.notes.txt",
+            "other.log",
+            "other.log.0",
+        ]
+    );
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn archives_keep_the_date_of_the_rotated_file() {
+    let dir = tempfile::tempdir().unwrap();
+    create_file(dir.path(), "_main.log.0", 3 * DAY);
+    let modified = std::fs::metadata(dir.path().join("_main.log.0")).unwrap().modified().unwrap();
+
+    let archive = Compression::Gzip.compress_file(&dir.path().join("_main.log.0")).unwrap();
+    assert_eq!(std::fs::metadata(archive).unwrap().modified().unwrap(), modified);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn keeps_backup_count_rotated_files_by_size_or_by_time() {
+    let dir = tempfile::tempdir().unwrap();
+    rotate_times(dir.path(), None, 2, 3).await;
+    assert_eq!(file_names(dir.path()), ["_main.log.0", "_main.log.1"]);
+    assert_eq!(std::fs::read_to_string(dir.path().join("_main.log.1")).unwrap(), "1 a\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    rotate_times(dir.path(), Some(RotationInterval::Hourly), 2, 3).await;
+    assert_eq!(file_names(dir.path()).len(), 2);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn keeps_no_rotated_file_with_a_zero_backup_count() {
+    for interval in [None, Some(RotationInterval::Hourly)] {
+        let dir = tempfile::tempdir().unwrap();
+        rotate_times(dir.path(), interval, 0, 2).await;
+        assert!(file_names(dir.path()).is_empty(), "{:?}", interval);
+    }
+}
@@ -0,0 +1,200 @@
+//! Rotation tests : period boundaries, rotated file names, size and time triggers, compression
+
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use log_server::core::compression::Compression;
+use log_server::core::records::LogRecord;
+use log_server::core::rotation::{RotationInterval, Rotator};
+use log_server::core::writers::{AckStage, LogWriter};
+use log_server::WriterConfig;
+
+
+
+
+const SOURCE: &str = "10.0.0.5:9020";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a UTC time
+fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, second).unwrap()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a rotator for `dir/_main.log` whose current file was last written at `modified`
+fn rotator(dir: &Path, interval: Option<RotationInterval>, max_file_bytes: u64, modified: DateTime<Utc>) -> Rotator {
+    let config = WriterConfig {
+        max_file_bytes,
+        rotation_interval: interval,
+        ..WriterConfig::default()
+    };
+    Rotator::new(&dir.join("_main.log"), &config, Some(modified))
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to list the file names of a directory, sorted
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_rotation_intervals() {
+    assert_eq!(RotationInterval::from_name("daily"), Some(RotationInterval::Daily));
+    assert_eq!(RotationInterval::from_name("hourly"), Some(RotationInterval::Hourly));
+    assert_eq!(RotationInterval::from_name("15m"), Some(RotationInterval::Minutes(15)));
+    assert_eq!(RotationInterval::from_name("0m"), None);
+    assert_eq!(RotationInterval::from_name("15"), None);
+    assert_eq!(RotationInterval::from_name("weekly"), None);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn computes_period_boundaries() {
+    let dir = tempfile::tempdir().unwrap();
+    let modified = at(2025, 1, 15, 10, 37, 12);
+
+    let minutes = rotator(dir.path(), Some(RotationInterval::Minutes(15)), 0, modified);
+    assert_eq!(minutes.next_boundary(), Some(at(2025, 1, 15, 10, 45, 0)));
+    assert!(!minutes.time_due(at(2025, 1, 15, 10, 44, 59)));
+    assert!(minutes.time_due(at(2025, 1, 15, 10, 45, 0)));
+
+    let hourly = rotator(dir.path(), Some(RotationInterval::Hourly), 0, modified);
+    assert_eq!(hourly.next_boundary(), Some(at(2025, 1, 15, 11, 0, 0)));
+
+    let daily = rotator(dir.path(), Some(RotationInterval::Daily), 0, modified);
+    assert_eq!(daily.next_boundary(), Some(at(2025, 1, 16, 0, 0, 0)));
+
+    // Size-only rotation has no boundary
+    let size_only = rotator(dir.path(), None, 100, modified);
+    assert_eq!(size_only.next_boundary(), None);
+    assert!(!size_only.time_due(at(2030, 1, 1, 0, 0, 0)));
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn names_rotated_files_after_their_period() {
+    let modified = at(2025, 1, 15, 10, 37, 12);
+    let cases = [
+        (RotationInterval::Minutes(15), "_main.2025-01-15T10-30.log"),
+        (RotationInterval::Hourly, "_main.2025-01-15T10.log"),
+        (RotationInterval::Daily, "_main.2025-01-15.log"),
+    ];
+
+    for (interval, expected) in cases {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("_main.log"), "0 a\n").unwrap();
+        let mut rotator = rotator(dir.path(), Some(interval), 0, modified);
+
+        let rotated = rotator.rotate(at(2025, 1, 16, 0, 0, 0)).await.unwrap();
+        rotator.wait_background().await;
+        assert_eq!(rotated, Some(dir.path().join(expected)));
+        assert_eq!(file_names(dir.path()), [expected]);
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rotates_on_size_and_on_period_end() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut rotator = rotator(dir.path(), Some(RotationInterval::Hourly), 100, at(2025, 1, 15, 10, 5, 0));
+
+    // Within the period, only size triggers
+    assert!(!rotator.size_due(99));
+    assert!(rotator.size_due(100));
+    assert!(!rotator.time_due(at(2025, 1, 15, 10, 59, 59)));
+
+    // A size rotation keeps the period, its boundary does not move
+    std::fs::write(dir.path().join("_main.log"), "0 a\n").unwrap();
+    rotator.rotate(at(2025, 1, 15, 10, 30, 0)).await.unwrap();
+    assert_eq!(rotator.next_boundary(), Some(at(2025, 1, 15, 11, 0, 0)));
+
+    // Reaching the boundary triggers, the next period starts
+    assert!(rotator.time_due(at(2025, 1, 15, 11, 0, 0)));
+    std::fs::write(dir.path().join("_main.log"), "1 b\n").unwrap();
+    rotator.rotate(at(2025, 1, 15, 11, 0, 0)).await.unwrap();
+    rotator.wait_background().await;
+    assert_eq!(rotator.next_boundary(), Some(at(2025, 1, 15, 12, 0, 0)));
+
+    assert_eq!(file_names(dir.path()), ["_main.2025-01-15T10.1.log", "_main.2025-01-15T10.log"]);
+    assert_eq!(std::fs::read_to_string(dir.path().join("_main.2025-01-15T10.log")).unwrap(), "0 a\n");
+    assert_eq!(std::fs::read_to_string(dir.path().join("_main.2025-01-15T10.1.log")).unwrap(), "1 b\n");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn keeps_compressed_archives_of_the_same_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = WriterConfig {
+        rotation_interval: Some(RotationInterval::Hourly),
+        compression: Some(Compression::Gzip),
+        ..WriterConfig::default()
+    };
+    let mut rotator = Rotator::new(&dir.path().join("_main.log"), &config, Some(at(2025, 1, 15, 10, 5, 0)));
+
+    // The first archive is compressed before the second rotation looks for a free name
+    for content in ["0 a\n", "1 b\n"] {
+        std::fs::write(dir.path().join("_main.log"), content).unwrap();
+        rotator.rotate(at(2025, 1, 15, 10, 30, 0)).await.unwrap();
+        rotator.wait_background().await;
+    }
+
+    assert_eq!(file_names(dir.path()), ["_main.2025-01-15T10.1.log.gz", "_main.2025-01-15T10.log.gz"]);
+    let read = |name: &str| Compression::Gzip.read_tail_lines(&dir.path().join(name), 1024).unwrap();
+    assert_eq!(read("_main.2025-01-15T10.log.gz"), ["0 a"]);
+    assert_eq!(read("_main.2025-01-15T10.1.log.gz"), ["1 b"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn compression_never_replaces_an_existing_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("_main.2025-01-15.log");
+    std::fs::write(&path, "1 b\n").unwrap();
+    std::fs::write(dir.path().join("_main.2025-01-15.log.zst"), "kept").unwrap();
+
+    let error = Compression::Zstd.compress_file(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(dir.path().join("_main.2025-01-15.log.zst")).unwrap(), "kept");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1 b\n");
+    assert_eq!(file_names(dir.path()), ["_main.2025-01-15.log", "_main.2025-01-15.log.zst"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn writer_rotates_on_size_within_the_current_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = WriterConfig {
+        log_path: dir.path().join("_main.log"),
+        max_file_bytes: 1,
+        rotation_interval: Some(RotationInterval::Daily),
+        ..WriterConfig::default()
+    };
+    let label = Utc::now().format("%Y-%m-%d").to_string();
+    let handle = LogWriter::new(config).await.unwrap().start_writer_task();
+
+    // Every record fills the file : the rotation after a record is done before the next one is written
+    for _ in 0..3 {
+        handle.send_and_wait(LogRecord::new(SOURCE), AckStage::Written).await.unwrap();
+    }
+
+    let first = std::fs::read_to_string(dir.path().join(format!("_main.{}.log", label))).unwrap();
+    let second = std::fs::read_to_string(dir.path().join(format!("_main.{}.1.log", label))).unwrap();
+    assert!(first.starts_with("0 "), "{}", first);
+    assert!(second.starts_with("1 "), "{}", second);
+}
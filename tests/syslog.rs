@@ -0,0 +1,149 @@
+//! Syslog tests : RFC 5424 / RFC 3164 parsing, TCP octet-counting framing and UDP rejection counters
+
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use bytes::BytesMut;
+use log_server::common::config::ServerConfig;
+use log_server::common::stats::ServerStats;
+use log_server::core::syslog::parse_syslog;
+use log_server::core::writers::WriterHandle;
+use log_server::network::syslog_server::{SyslogCodec, SyslogServer};
+use tokio_util::codec::Decoder;
+
+
+
+
+const SOURCE: &str = "10.0.0.7:514";
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to feed `stream` to one decoder split at `split`, collecting every decoded message
+fn decode_split(stream: &[u8], split: usize) -> Vec<Vec<u8>> {
+    let mut codec = SyslogCodec::new(1024);
+    let mut buffer = BytesMut::new();
+    let mut messages = Vec::new();
+    for chunk in [&stream[..split], &stream[split..]] {
+        buffer.extend_from_slice(chunk);
+        while let Some(message) = codec.decode(&mut buffer).unwrap() {
+            messages.push(message.to_vec());
+        }
+    }
+    if let Some(message) = codec.decode_eof(&mut buffer).unwrap() {
+        messages.push(message.to_vec());
+    }
+    messages
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_rfc5424_message() {
+    let record = parse_syslog(
+        b"<165>1 2025-01-15T10:30:45.123Z gw01 routerd 4242 ID47 [origin ip=\"10.0.0.1\"] \xef\xbb\xbfLink down",
+        SOURCE,
+    )
+    .unwrap();
+
+    assert_eq!(record.level_name(), "INFO");
+    assert_eq!(record.module, "local4");
+    assert_eq!(record.timestamp, "2025-01-15T10:30:45.123Z");
+    assert_eq!(record.hostname, "gw01");
+    assert_eq!(record.logger_name, "routerd");
+    assert_eq!(record.process_id, "4242");
+    assert_eq!(record.message, "ID47 [origin ip=\"10.0.0.1\"] Link down");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_rfc5424_message_with_nil_values() {
+    let record = parse_syslog(b"<11>1 - - - - - - disk failure", SOURCE).unwrap();
+
+    assert_eq!(record.level_name(), "ERROR");
+    assert_eq!(record.module, "user");
+    assert_eq!(record.timestamp, "");
+    assert_eq!(record.hostname, "10.0.0.7");
+    assert_eq!(record.logger_name, "");
+    assert_eq!(record.message, "disk failure");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_rfc3164_message() {
+    let record = parse_syslog(b"<34>Oct  1 22:14:15 mymachine su[231]: 'su root' failed", SOURCE).unwrap();
+
+    assert_eq!(record.level_name(), "CRITICAL");
+    assert_eq!(record.module, "auth");
+    assert!(record.timestamp.ends_with("-10-01T22:14:15"), "{}", record.timestamp);
+    assert_eq!(record.hostname, "mymachine");
+    assert_eq!(record.logger_name, "su");
+    assert_eq!(record.process_id, "231");
+    assert_eq!(record.message, "'su root' failed");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn parses_rfc3164_message_without_hostname_or_priority() {
+    let record = parse_syslog(b"Oct 11 09:00:00 cron: job started", SOURCE).unwrap();
+
+    assert_eq!(record.level_name(), "INFO");
+    assert_eq!(record.module, "user");
+    assert_eq!(record.hostname, "10.0.0.7");
+    assert_eq!(record.logger_name, "cron");
+    assert_eq!(record.message, "job started");
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_invalid_priority() {
+    assert!(parse_syslog(b"<192>1 - - - - - - too high", SOURCE).is_err());
+    assert!(parse_syslog(b"<abc>message", SOURCE).is_err());
+    assert!(parse_syslog(b"\n", SOURCE).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn decodes_octet_counted_and_newline_frames_at_every_split() {
+    let stream = b"11 <13>1 - - a\n<13>legacy line\r\n9 <13>1 - b";
+    let expected: Vec<Vec<u8>> = vec![b"<13>1 - - a".to_vec(), b"<13>legacy line\r".to_vec(), b"<13>1 - b".to_vec()];
+
+    for split in 0..=stream.len() {
+        assert_eq!(decode_split(stream, split), expected, "split at {}", split);
+    }
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn rejects_oversized_octet_count() {
+    let mut codec = SyslogCodec::new(1024);
+    let mut buffer = BytesMut::from(&b"2048 <13>1 -"[..]);
+
+    assert!(codec.decode(&mut buffer).is_err());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn counts_malformed_and_dropped_datagrams_in_syslog_counters() {
+    let config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    let (writer, mut records) = WriterHandle::detached(1);
+    let stats = Arc::new(ServerStats::default());
+    let server = SyslogServer::new(&config, writer, stats.clone());
+    let sender = SOURCE.parse().unwrap();
+
+    server.handle_datagram(b"<13>1 - host app - - - first", sender);
+    server.handle_datagram(b"<999>1 - host app - - - invalid", sender);
+    server.handle_datagram(b"<13>1 - host app - - - queue full", sender);
+
+    assert_eq!(records.try_recv().unwrap().source, SOURCE);
+    assert!(records.try_recv().is_none());
+    assert_eq!(stats.malformed_syslog_messages.load(Ordering::Relaxed), 1);
+    assert_eq!(stats.dropped_syslog_messages.load(Ordering::Relaxed), 1);
+    assert_eq!(stats.dropped_datagrams.load(Ordering::Relaxed), 0);
+}
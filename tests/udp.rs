@@ -0,0 +1,113 @@
+//! UDP tests : datagram decoding, rejection counters and drop on a full writer queue
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use capnp::{message, serialize_packed};
+use log_server::common::config::ServerConfig;
+use log_server::common::stats::{report_rejected, ServerStats, REPORT_EVERY};
+use log_server::core::writers::{QueuedRecords, WriterHandle};
+use log_server::logger_capnp::logger_msg::{logger_msg, Level};
+use log_server::network::udp_server::UdpServer;
+
+
+
+
+const MAX_DATAGRAM_BYTES: usize = 512;
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a packed `LoggerMsg` datagram
+fn datagram(message: &str) -> Vec<u8> {
+    let mut builder = message::Builder::new_default();
+    let mut log_message = builder.init_root::<logger_msg::Builder<'_>>();
+    log_message.set_message(message);
+    log_message.set_level(Level::Info);
+    let mut data = Vec::new();
+    serialize_packed::write_message(&mut data, &builder).unwrap();
+    data
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper to build a UDP server over a detached writer queue of `capacity` records
+fn server(capacity: usize) -> (UdpServer, QueuedRecords, Arc<ServerStats>) {
+    let mut config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    config.max_datagram_bytes = MAX_DATAGRAM_BYTES;
+    let (writer, records) = WriterHandle::detached(capacity);
+    let stats = Arc::new(ServerStats::default());
+    (UdpServer::new(&config, writer, stats.clone()), records, stats)
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper giving the sender address of the datagrams
+fn sender() -> SocketAddr {
+    "10.0.0.5:40000".parse().unwrap()
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn queues_decoded_datagram_with_sender_as_source() {
+    let (server, mut records, stats) = server(8);
+
+    server.handle_datagram(&datagram("disk almost full"), sender());
+
+    let record = records.try_recv().unwrap();
+    assert_eq!(record.message, "disk almost full");
+    assert_eq!(record.level_name(), "INFO");
+    assert_eq!(record.source, "10.0.0.5:40000");
+    assert_eq!(record.sequence, 0);
+    assert_eq!(stats.malformed_datagrams.load(Ordering::Relaxed), 0);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn counts_malformed_and_oversized_datagrams() {
+    let (server, mut records, stats) = server(8);
+
+    server.handle_datagram(b"\xff\xffnot capnp", sender());
+    server.handle_datagram(&datagram(&"x".repeat(MAX_DATAGRAM_BYTES)), sender());
+
+    assert!(records.try_recv().is_none());
+    assert_eq!(stats.malformed_datagrams.load(Ordering::Relaxed), 1);
+    assert_eq!(stats.dropped_datagrams.load(Ordering::Relaxed), 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn drops_datagrams_without_sequence_gap_when_queue_is_full() {
+    let (server, mut records, stats) = server(1);
+
+    server.handle_datagram(&datagram("first"), sender());
+    server.handle_datagram(&datagram("second"), sender());
+    assert_eq!(stats.dropped_datagrams.load(Ordering::Relaxed), 1);
+
+    assert_eq!(records.try_recv().unwrap().message, "first");
+    server.handle_datagram(&datagram("third"), sender());
+    let third = records.try_recv().unwrap();
+    assert_eq!(third.message, "third");
+    assert_eq!(third.sequence, 1);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[test]
+fn reports_first_rejection_then_once_per_report_every() {
+    let counter = AtomicU64::new(0);
+
+    assert!(report_rejected("test", &counter, 1, "dropped", "datagram"));
+    let reported = (1..REPORT_EVERY)
+        .filter(|_| report_rejected("test", &counter, 1, "dropped", "datagram"))
+        .count();
+    assert_eq!(reported, 1);
+    assert_eq!(counter.load(Ordering::Relaxed), REPORT_EVERY);
+
+    // A batch crossing a multiple is reported once
+    assert!(!report_rejected("test", &counter, REPORT_EVERY - 1, "incomplete", "messages"));
+    assert!(report_rejected("test", &counter, 2, "incomplete", "messages"));
+}
@@ -0,0 +1,102 @@
+//! Unix socket tests : socket permissions, stale and live sockets at the path
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log_server::common::config::ServerConfig;
+use log_server::common::stats::ServerStats;
+use log_server::core::writers::WriterHandle;
+use log_server::network::unix_server::UnixServer;
+use tokio::task::JoinHandle;
+
+
+
+
+// Helper to run a Unix socket server on `path` with `mode`, returns its task and outcome
+fn run_server(path: &Path, mode: u32) -> JoinHandle<Result<(), String>> {
+    let mut config = ServerConfig::new("test", "127.0.0.1", 0, 0);
+    config.unix_socket = Some(path.to_path_buf());
+    config.unix_socket_mode = mode;
+    let (writer, _records) = WriterHandle::detached(8);
+    let server = UnixServer::new(&config, writer, Arc::new(ServerStats::default()));
+    tokio::spawn(async move { server.run().await.map_err(|e| e.to_string()) })
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper waiting until a server accepts connections on `path`
+async fn wait_listening(path: &Path) {
+    for _ in 0..200 {
+        if tokio::net::UnixStream::connect(path).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("no server listening on {}", path.display());
+}
+
+//-----------------------------------------------------------------------------------------------
+
+// Helper giving the socket path in a temporary directory
+fn socket_path(dir: &tempfile::TempDir) -> PathBuf {
+    dir.path().join("log_server.sock")
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn applies_the_socket_mode_and_leaves_no_bind_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_path(&dir);
+    let _server = run_server(&path, 0o600);
+    wait_listening(&path).await;
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode, 0o600);
+    let names: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    assert_eq!(names, ["log_server.sock"]);
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn replaces_a_stale_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_path(&dir);
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let _server = run_server(&path, 0o660);
+    wait_listening(&path).await;
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn refuses_a_socket_another_server_listens_on() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_path(&dir);
+    let live = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+    let error = run_server(&path, 0o660).await.unwrap().unwrap_err();
+    assert!(error.contains("in use by another server"), "{}", error);
+
+    // The other server keeps its socket
+    std::os::unix::net::UnixStream::connect(&path).unwrap();
+    live.accept().unwrap();
+}
+
+//-----------------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn refuses_a_path_that_is_not_a_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_path(&dir);
+    std::fs::write(&path, "not a socket").unwrap();
+
+    let error = run_server(&path, 0o660).await.unwrap().unwrap_err();
+    assert!(error.contains("is not a socket"), "{}", error);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+}